@@ -0,0 +1,218 @@
+//! Rendering of the [Backtrace] attached to a [crate::UserError].
+use std::backtrace::Backtrace;
+use std::env;
+use std::fmt;
+
+use crate::style::Styles;
+use crate::theme::Theme;
+
+/// Symbol prefixes of frames belonging to the runtime or to this crate, hidden unless the
+/// backtrace style is `full`.
+const HIDDEN_PREFIXES: &[&str] = &[
+    "std::",
+    "core::",
+    "alloc::",
+    "uerr::",
+    "backtrace::",
+    "__rust",
+    "rust_begin_unwind",
+];
+
+/// Symbols of the process entry points, hidden unless the backtrace style is `full`.
+const HIDDEN_SYMBOLS: &[&str] = &[
+    "main",
+    "_start",
+    "__libc_start_main",
+    "__libc_start_call_main",
+    "<unknown>",
+];
+
+struct Frame<'a> {
+    symbol: &'a str,
+    location: Option<&'a str>,
+}
+
+impl Frame<'_> {
+    fn is_hidden(&self) -> bool {
+        if HIDDEN_SYMBOLS.contains(&self.symbol) {
+            return true;
+        }
+
+        // A symbol such as `<usize as core::slice::index::SliceIndex<[T]>>::index` belongs to the
+        // crate of its self type; primitives, references and function pointers are treated as the
+        // standard library.
+        let path = match self.symbol.strip_prefix('<') {
+            Some(qualified) => {
+                let ty = qualified.split(" as ").next().unwrap_or(qualified);
+                let ty = ty.trim_start_matches(['&', '[', '(', '*']);
+                let ty = ty
+                    .trim_start_matches("mut ")
+                    .trim_start_matches("const ")
+                    .trim_start_matches("dyn ");
+
+                if !ty.contains("::") || ty.starts_with("fn(") {
+                    return true;
+                }
+                ty
+            }
+            None => self.symbol,
+        };
+
+        HIDDEN_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+    }
+}
+
+/// Split the [fmt::Display] text of a [Backtrace] into its frames.
+///
+/// Inlined symbols, which share the index of their frame, are returned as frames of their own.
+fn frames(text: &str) -> Vec<Frame<'_>> {
+    let mut frames: Vec<Frame> = Vec::new();
+
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if let Some(location) = line.strip_prefix("at ") {
+            if let Some(frame) = frames.last_mut() {
+                frame.location = Some(location);
+            }
+            continue;
+        }
+
+        let symbol = match line.split_once(": ") {
+            Some((index, symbol)) if index.bytes().all(|b| b.is_ascii_digit()) => symbol,
+            _ => line,
+        };
+
+        frames.push(Frame {
+            symbol,
+            location: None,
+        });
+    }
+    frames
+}
+
+/// Whether `RUST_LIB_BACKTRACE`, or `RUST_BACKTRACE` in its absence, asks for full backtraces.
+fn is_full() -> bool {
+    env::var_os("RUST_LIB_BACKTRACE")
+        .or_else(|| env::var_os("RUST_BACKTRACE"))
+        .is_some_and(|style| style == "full")
+}
+
+/// Render the given [Backtrace] as a section beneath the help of a [crate::UserError].
+///
+/// Frames of the standard library, of this crate and of the process entry points are collapsed
+/// into a single line, unless the backtrace style is `full`.
+pub(crate) fn render<W>(
+    w: &mut W,
+    backtrace: &Backtrace,
+    theme: &Theme,
+    styles: &Styles,
+) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
+    let text = backtrace.to_string();
+    render_text(w, &text, is_full(), theme, styles)
+}
+
+fn render_text<W>(w: &mut W, text: &str, full: bool, theme: &Theme, styles: &Styles) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
+    let indent = " ".repeat(theme.indent);
+    let mut shown = 0;
+    let mut hidden = 0;
+
+    writeln!(w, "{indent}{}", styles.gutter.paint("backtrace:"))?;
+
+    for frame in frames(text) {
+        if !full && frame.is_hidden() {
+            hidden += 1;
+            continue;
+        }
+
+        writeln!(
+            w,
+            "{indent}{}: {}",
+            styles.gutter.paint(format_args!("{shown:>4}")),
+            frame.symbol
+        )?;
+        shown += 1;
+
+        if let Some(location) = frame.location {
+            writeln!(w, "{indent}            at {location}")?;
+        }
+    }
+
+    if hidden > 0 {
+        let s = if hidden == 1 { "" } else { "s" };
+
+        writeln!(
+            w,
+            "{indent}{}",
+            styles.gutter.paint(format_args!(
+                "({hidden} frame{s} hidden; set `RUST_BACKTRACE=full` to show them)"
+            ))
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_text;
+    use crate::style::Styles;
+    use crate::theme::Theme;
+
+    const TEXT: &str = "   0: std::backtrace::Backtrace::capture
+             at /rustc/library/std/src/backtrace.rs:296:9
+   1: uerr::UserError::new
+             at ./src/lib.rs:540:24
+   2: app::config::load
+             at ./src/config.rs:42:9
+      app::config::parse
+             at ./src/config.rs:12:5
+   3: app::main
+             at ./src/main.rs:4:5
+   4: core::ops::function::FnOnce::call_once
+             at /rustc/library/core/src/ops/function.rs:250:5
+   5: <&dyn core::ops::function::Fn<(), Output = i32> as core::ops::function::FnOnce<()>>::call_once
+   6: <usize as core::slice::index::SliceIndex<[T]>>::index
+   7: <app::Config as core::fmt::Display>::fmt
+             at ./src/config.rs:80:9
+   8: main
+   9: __libc_start_main
+  10: _start
+";
+
+    #[test]
+    fn filters_frames() {
+        let mut s = String::new();
+        render_text(&mut s, TEXT, false, &Theme::ASCII, &Styles::PLAIN).unwrap();
+
+        assert_eq!(
+            s,
+            " backtrace:
+    0: app::config::load
+             at ./src/config.rs:42:9
+    1: app::config::parse
+             at ./src/config.rs:12:5
+    2: app::main
+             at ./src/main.rs:4:5
+    3: <app::Config as core::fmt::Display>::fmt
+             at ./src/config.rs:80:9
+ (8 frames hidden; set `RUST_BACKTRACE=full` to show them)
+"
+        );
+    }
+
+    #[test]
+    fn full() {
+        let mut s = String::new();
+        render_text(&mut s, TEXT, true, &Theme::ASCII, &Styles::PLAIN).unwrap();
+
+        assert!(s.contains("    0: std::backtrace::Backtrace::capture\n"));
+        assert!(s.contains("   11: _start\n"));
+        assert!(!s.contains("hidden"));
+    }
+}
@@ -0,0 +1,151 @@
+//! Stable error codes and a catalog of their long-form explanations.
+//!
+//! Codes are attached with [UserError::and_code](crate::UserError::and_code) and rendered next to
+//! the prefix, such as `error[E0042]: ...`. Binaries can register explanations for their codes
+//! and implement a `--explain` flag on top of [print_explanation].
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io::{self, Write as _};
+use std::sync::{PoisonError, RwLock};
+
+use crate::style;
+use crate::theme;
+use crate::UserError;
+
+static CATALOG: RwLock<Catalog> = RwLock::new(Catalog::new());
+
+/// A mapping of error codes to their long-form explanations.
+/// # Examples
+/// ```
+/// use uerr::code::Catalog;
+///
+/// let catalog = Catalog::new()
+///     .and_entry("E0042", "The configuration file could not be found.");
+///
+/// assert_eq!(
+///     catalog.explain("E0042").unwrap(),
+///     "E0042\n\nThe configuration file could not be found.\n"
+/// );
+/// assert!(catalog.explain("E9999").is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Catalog {
+    entries: BTreeMap<String, String>,
+}
+
+impl Catalog {
+    /// Create a new, empty Catalog.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Add an explanation for the given code, replacing any previous explanation.
+    #[inline]
+    pub fn add_entry(&mut self, code: impl Into<String>, explanation: impl Into<String>) {
+        self.entries.insert(code.into(), explanation.into());
+    }
+
+    /// Add an explanation for the given code, replacing any previous explanation.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn and_entry(mut self, code: impl Into<String>, explanation: impl Into<String>) -> Self {
+        self.add_entry(code, explanation);
+        self
+    }
+
+    /// Get the explanation of the given code.
+    #[inline]
+    pub fn get(&self, code: &str) -> Option<&str> {
+        self.entries.get(code.trim()).map(String::as_str)
+    }
+
+    /// Iterate over the codes in this Catalog, in ascending order.
+    #[inline]
+    pub fn codes(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    fn render(&self, code: &str, colored: bool) -> Result<String, UserError> {
+        let explanation = self.get(code).ok_or_else(|| {
+            UserError::new(format!("no explanation found for `{}`", code.trim()))
+                .and_help("Check that the code was copied correctly.")
+        })?;
+
+        let styles = theme::with_theme(|theme| theme.styles);
+        let heading = if colored {
+            styles.message
+        } else {
+            style::Style::new()
+        };
+
+        let mut s = String::new();
+        let _ = writeln!(s, "{}\n", heading.paint(code.trim()));
+        let _ = writeln!(s, "{}", explanation.trim_end());
+        Ok(s)
+    }
+
+    /// Render the explanation of the given code without colors.
+    ///
+    /// Returns an error describing the unknown code if it is not within this Catalog.
+    #[inline]
+    pub fn explain(&self, code: &str) -> Result<String, UserError> {
+        self.render(code, false)
+    }
+
+    /// Print the explanation of the given code to stdout.
+    ///
+    /// Colors are emitted according to the global [style::ColorChoice]. Returns an error
+    /// describing the unknown code if it is not within this Catalog.
+    pub fn print_explanation(&self, code: &str) -> Result<(), UserError> {
+        let stdout = io::stdout();
+        let text = self.render(code, style::color_choice().enabled_for(&stdout))?;
+
+        let _ = stdout.lock().write_all(text.as_bytes());
+        Ok(())
+    }
+}
+
+/// Register an explanation for the given code in the global [Catalog].
+pub fn register(code: impl Into<String>, explanation: impl Into<String>) {
+    CATALOG
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .add_entry(code, explanation);
+}
+
+/// Get a copy of the explanation of the given code from the global [Catalog].
+pub fn explanation(code: &str) -> Option<String> {
+    with_catalog(|catalog| catalog.get(code).map(str::to_string))
+}
+
+/// Render the explanation of the given code from the global [Catalog]. See [Catalog::explain].
+pub fn explain(code: &str) -> Result<String, UserError> {
+    with_catalog(|catalog| catalog.explain(code))
+}
+
+/// Print the explanation of the given code from the global [Catalog] to stdout.
+/// ```no_run
+/// uerr::code::register("E0042", "The configuration file could not be found.");
+///
+/// if let Some(code) = std::env::args().skip_while(|arg| arg != "--explain").nth(1) {
+///     if let Err(err) = uerr::code::print_explanation(&code) {
+///         err.print().exit(1);
+///     }
+/// }
+/// ```
+///
+/// See [Catalog::print_explanation].
+pub fn print_explanation(code: &str) -> Result<(), UserError> {
+    with_catalog(|catalog| catalog.print_explanation(code))
+}
+
+fn with_catalog<F, R>(f: F) -> R
+where
+    F: FnOnce(&Catalog) -> R,
+{
+    f(&CATALOG.read().unwrap_or_else(PoisonError::into_inner))
+}
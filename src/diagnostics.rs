@@ -0,0 +1,284 @@
+//! A collector for reporting many [UserError]s at once.
+use std::num::NonZeroUsize;
+
+use crate::exit::{self, ExitCode};
+use crate::severity::Severity;
+use crate::UserError;
+
+/// Accumulates [UserError]s, such as those found by a validator in a single pass, and renders
+/// them together followed by a summary.
+///
+/// Errors are the diagnostics whose [Severity] is fatal; see [Severity::is_fatal].
+/// # Examples
+/// ```no_run
+/// use std::num::NonZeroUsize;
+/// use uerr::{Diagnostics, UserError};
+///
+/// let mut diagnostics = Diagnostics::new().and_max_errors(NonZeroUsize::new(20).unwrap());
+///
+/// diagnostics.push(UserError::from("missing key `name`"));
+/// diagnostics.push(UserError::warning("unused key `colour`"));
+///
+/// diagnostics.print().exit_if_errors();
+/// ```
+/// ```text
+/// error: missing key `name`
+/// warning: unused key `colour`
+/// error: aborting due to 1 previous error; 1 warning emitted
+/// ```
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    diagnostics: Vec<UserError>,
+    max_errors: Option<NonZeroUsize>,
+}
+
+impl Diagnostics {
+    /// Create a new, empty Diagnostics without a maximum error count.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            diagnostics: Vec::new(),
+            max_errors: None,
+        }
+    }
+
+    /// Set the number of errors at which [Diagnostics::push] aborts.
+    #[inline]
+    pub fn set_max_errors(&mut self, max: NonZeroUsize) {
+        self.max_errors = Some(max);
+    }
+
+    /// Set the number of errors at which [Diagnostics::push] aborts.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn and_max_errors(mut self, max: NonZeroUsize) -> Self {
+        self.max_errors = Some(max);
+        self
+    }
+
+    /// Add a diagnostic.
+    ///
+    /// If the diagnostic is an error which brings the number of errors to the maximum, every
+    /// diagnostic is printed and the process exits; see [Diagnostics::terminate].
+    pub fn push(&mut self, diagnostic: impl Into<UserError>) {
+        let diagnostic = diagnostic.into();
+        let fatal = diagnostic.severity().is_fatal();
+
+        self.diagnostics.push(diagnostic);
+
+        if !fatal {
+            return;
+        }
+
+        if self
+            .max_errors
+            .is_some_and(|max| self.error_count() >= max.get())
+        {
+            self.print().terminate();
+        }
+    }
+
+    /// The number of diagnostics with a fatal [Severity].
+    pub fn error_count(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity().is_fatal())
+            .count()
+    }
+
+    /// The number of diagnostics with [Severity::Warning].
+    pub fn warning_count(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| *diagnostic.severity() == Severity::Warning)
+            .count()
+    }
+
+    /// Returns true if any diagnostic has a fatal [Severity].
+    #[inline]
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    #[inline]
+    pub const fn diagnostics(&self) -> &Vec<UserError> {
+        &self.diagnostics
+    }
+
+    /// The summary printed after the diagnostics, such as
+    /// `aborting due to 3 previous errors; 2 warnings emitted`.
+    ///
+    /// The summary is an error if there are errors, a warning if there are only warnings, and
+    /// [None] otherwise. It carries neither a backtrace nor a location, as it describes the
+    /// diagnostics rather than a failure of its own.
+    pub fn summary(&self) -> Option<UserError> {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        let errors = self.error_count();
+        let warnings = self.warning_count();
+
+        let warnings =
+            (warnings > 0).then(|| format!("{warnings} warning{} emitted", plural(warnings)));
+
+        let mut summary = match (errors, warnings) {
+            (0, None) => return None,
+            (0, Some(warnings)) => {
+                UserError::without_backtrace(warnings).and_severity(Severity::Warning)
+            }
+            (errors, warnings) => {
+                let mut message =
+                    format!("aborting due to {errors} previous error{}", plural(errors));
+
+                if let Some(warnings) = warnings {
+                    message.push_str("; ");
+                    message.push_str(&warnings);
+                }
+                UserError::without_backtrace(message)
+            }
+        };
+
+        summary.details.location = None;
+        Some(summary)
+    }
+
+    /// The [ExitCode] to exit with, or [None] if there are no errors.
+    ///
+    /// This is the highest code among the errors, so that a specific code such as
+    /// [ExitCode::Config] takes precedence over the default [ExitCode::Failure].
+    pub fn exit_code(&self) -> Option<ExitCode> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity().is_fatal())
+            .map(UserError::exit_code_or_default)
+            .max_by_key(|code| code.code())
+    }
+
+    /// Print every diagnostic with [UserError::print], followed by the summary.
+    pub fn print(&self) -> &Self {
+        for diagnostic in &self.diagnostics {
+            diagnostic.print();
+        }
+
+        if let Some(summary) = self.summary() {
+            summary.print();
+        }
+        self
+    }
+
+    /// Exit the process with the [ExitCode] of the errors, or [ExitCode::Ok] if there are none.
+    ///
+    /// See [Diagnostics::exit_code] and [exit::exit].
+    #[inline]
+    pub fn terminate(&self) -> ! {
+        exit::exit(self.exit_code().unwrap_or(ExitCode::Ok));
+    }
+
+    /// Exit the process if there are any errors.
+    ///
+    /// Returns the current instance otherwise. See [Diagnostics::terminate].
+    #[inline]
+    pub fn exit_if_errors(&self) -> &Self {
+        if self.has_errors() {
+            self.terminate();
+        }
+        self
+    }
+}
+
+impl<E> Extend<E> for Diagnostics
+where
+    E: Into<UserError>,
+{
+    /// Add each diagnostic with [Diagnostics::push].
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = E>,
+    {
+        for diagnostic in iter {
+            self.push(diagnostic);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::Diagnostics;
+    use crate::exit::ExitCode;
+    use crate::testing::expect_exit;
+    use crate::UserError;
+
+    #[test]
+    fn summary() {
+        let mut diagnostics = Diagnostics::new();
+        assert!(diagnostics.summary().is_none());
+
+        diagnostics.push(UserError::warning("unused key"));
+        assert_eq!(
+            diagnostics.summary().unwrap().message(),
+            "1 warning emitted"
+        );
+
+        diagnostics.extend([
+            UserError::from("missing key").and_exit_code(ExitCode::Config),
+            UserError::from("bad value"),
+            UserError::warning("deprecated key"),
+            UserError::note("see the manual"),
+        ]);
+
+        let summary = diagnostics.summary().unwrap();
+
+        assert_eq!(
+            summary.message(),
+            "aborting due to 2 previous errors; 2 warnings emitted"
+        );
+        assert!(summary.severity().is_fatal());
+        assert!(summary.backtrace().is_none());
+        assert!(summary.location().is_none());
+        assert_eq!(diagnostics.exit_code(), Some(ExitCode::Config));
+    }
+
+    #[test]
+    fn max_errors() {
+        let exit = expect_exit(|| {
+            let mut diagnostics = Diagnostics::new().and_max_errors(NonZeroUsize::new(2).unwrap());
+
+            diagnostics.push(UserError::from("first"));
+            diagnostics.push(UserError::warning("unused"));
+            diagnostics.push(UserError::from("second"));
+            diagnostics.push(UserError::from("unreachable"));
+        });
+
+        assert_eq!(exit.code(), 1);
+        assert_eq!(
+            exit.output(),
+            "error: first
+warning: unused
+error: second
+error: aborting due to 2 previous errors; 1 warning emitted
+"
+        );
+    }
+
+    #[test]
+    fn max_errors_ignores_warnings() {
+        let mut diagnostics = Diagnostics::new().and_max_errors(NonZeroUsize::MIN);
+
+        diagnostics.push(UserError::warning("unused"));
+        diagnostics.push(UserError::note("see the manual"));
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(!diagnostics.has_errors());
+    }
+}
@@ -0,0 +1,339 @@
+//! Exit codes and hooks used when terminating the process on an error.
+use std::fmt::{self, Display};
+use std::io::{self, ErrorKind, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use crate::UserError;
+
+type Hook = Box<dyn FnOnce() + Send + 'static>;
+
+static HOOKS: Mutex<Vec<(u64, Hook)>> = Mutex::new(Vec::new());
+static NEXT_HOOK: AtomicU64 = AtomicU64::new(0);
+
+/// Exit codes mirroring BSD `sysexits.h`, which shell scripts can rely on.
+/// # Examples
+/// ```
+/// use std::io::ErrorKind;
+/// use uerr::exit::ExitCode;
+///
+/// assert_eq!(i32::from(ExitCode::Config), 78);
+/// assert_eq!(ExitCode::from(ErrorKind::NotFound), ExitCode::NoInput);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExitCode {
+    /// Successful termination, `0`.
+    Ok,
+    /// A general failure, `1`.
+    Failure,
+    /// The command was used incorrectly, `64`.
+    Usage,
+    /// The input data was incorrect, `65`.
+    DataErr,
+    /// An input file did not exist or was not readable, `66`.
+    NoInput,
+    /// The specified user did not exist, `67`.
+    NoUser,
+    /// The specified host did not exist, `68`.
+    NoHost,
+    /// A service is unavailable, `69`.
+    Unavailable,
+    /// An internal software error was detected, `70`.
+    Software,
+    /// An operating system error was detected, `71`.
+    OsErr,
+    /// A system file did not exist or was not readable, `72`.
+    OsFile,
+    /// A user specified output file could not be created, `73`.
+    CantCreat,
+    /// An error occurred while doing I/O, `74`.
+    IoErr,
+    /// A temporary failure; the user is invited to retry, `75`.
+    TempFail,
+    /// The remote system returned something invalid during a protocol exchange, `76`.
+    Protocol,
+    /// Insufficient permission to perform the operation, `77`.
+    NoPerm,
+    /// Something was found in an unconfigured or misconfigured state, `78`.
+    Config,
+    /// Any other exit code.
+    Other(i32),
+}
+
+impl ExitCode {
+    /// The numeric value of this ExitCode.
+    pub const fn code(self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::Failure => 1,
+            Self::Usage => 64,
+            Self::DataErr => 65,
+            Self::NoInput => 66,
+            Self::NoUser => 67,
+            Self::NoHost => 68,
+            Self::Unavailable => 69,
+            Self::Software => 70,
+            Self::OsErr => 71,
+            Self::OsFile => 72,
+            Self::CantCreat => 73,
+            Self::IoErr => 74,
+            Self::TempFail => 75,
+            Self::Protocol => 76,
+            Self::NoPerm => 77,
+            Self::Config => 78,
+            Self::Other(code) => code,
+        }
+    }
+}
+
+impl From<ExitCode> for i32 {
+    #[inline]
+    fn from(code: ExitCode) -> Self {
+        code.code()
+    }
+}
+
+impl From<i32> for ExitCode {
+    /// The variant with the given numeric value, or [ExitCode::Other].
+    fn from(code: i32) -> Self {
+        match code {
+            0 => Self::Ok,
+            1 => Self::Failure,
+            64 => Self::Usage,
+            65 => Self::DataErr,
+            66 => Self::NoInput,
+            67 => Self::NoUser,
+            68 => Self::NoHost,
+            69 => Self::Unavailable,
+            70 => Self::Software,
+            71 => Self::OsErr,
+            72 => Self::OsFile,
+            73 => Self::CantCreat,
+            74 => Self::IoErr,
+            75 => Self::TempFail,
+            76 => Self::Protocol,
+            77 => Self::NoPerm,
+            78 => Self::Config,
+            code => Self::Other(code),
+        }
+    }
+}
+
+impl From<ErrorKind> for ExitCode {
+    /// The conventional exit code of an I/O failure of the given kind.
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::NotFound | ErrorKind::NotADirectory | ErrorKind::IsADirectory => {
+                Self::NoInput
+            }
+            ErrorKind::PermissionDenied => Self::NoPerm,
+            ErrorKind::AlreadyExists
+            | ErrorKind::DirectoryNotEmpty
+            | ErrorKind::ReadOnlyFilesystem => Self::CantCreat,
+            ErrorKind::ConnectionRefused
+            | ErrorKind::AddrInUse
+            | ErrorKind::AddrNotAvailable
+            | ErrorKind::Unsupported => Self::Unavailable,
+            ErrorKind::TimedOut | ErrorKind::WouldBlock | ErrorKind::Interrupted => Self::TempFail,
+            ErrorKind::InvalidInput | ErrorKind::InvalidData | ErrorKind::UnexpectedEof => {
+                Self::DataErr
+            }
+            ErrorKind::OutOfMemory => Self::OsErr,
+            _ => Self::IoErr,
+        }
+    }
+}
+
+impl Display for ExitCode {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.code().fmt(f)
+    }
+}
+
+/// A trait marking an error type as able to decide its own exit code.
+pub trait ToExitCode {
+    /// The exit code the process should terminate with because of this error.
+    fn exit_code(&self) -> i32;
+}
+
+impl ToExitCode for io::Error {
+    /// The [ExitCode] of the kind of this error.
+    #[inline]
+    fn exit_code(&self) -> i32 {
+        ExitCode::from(self.kind()).code()
+    }
+}
+
+impl ToExitCode for UserError {
+    /// The stored [ExitCode], or [ExitCode::Failure] if there is none.
+    #[inline]
+    fn exit_code(&self) -> i32 {
+        self.exit_code_or_default().code()
+    }
+}
+
+impl ToExitCode for ExitCode {
+    #[inline]
+    fn exit_code(&self) -> i32 {
+        self.code()
+    }
+}
+
+/// Decides the exit code used by [unwrap_or_exit](crate::unwrap_or_exit) for an error.
+///
+/// This is implemented for:
+/// - [i32] and [ExitCode], which always exit with that code.
+/// - [FromError], which asks the error through [ToExitCode], such as the stored code of a
+///   [UserError].
+/// - Any `Fn(&E) -> i32`, which maps the error to a code.
+pub trait ExitCodeStrategy<E> {
+    /// The exit code to terminate with because of the given error.
+    fn exit_code(&self, err: &E) -> i32;
+}
+
+impl<E> ExitCodeStrategy<E> for i32 {
+    #[inline]
+    fn exit_code(&self, _: &E) -> i32 {
+        *self
+    }
+}
+
+impl<E> ExitCodeStrategy<E> for ExitCode {
+    #[inline]
+    fn exit_code(&self, _: &E) -> i32 {
+        self.code()
+    }
+}
+
+impl<E, F> ExitCodeStrategy<E> for F
+where
+    F: Fn(&E) -> i32,
+{
+    #[inline]
+    fn exit_code(&self, err: &E) -> i32 {
+        self(err)
+    }
+}
+
+/// An [ExitCodeStrategy] which asks the error for its code through [ToExitCode].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FromError;
+
+impl<E> ExitCodeStrategy<E> for FromError
+where
+    E: ToExitCode,
+{
+    #[inline]
+    fn exit_code(&self, err: &E) -> i32 {
+        err.exit_code()
+    }
+}
+
+/// Identifies a hook registered with [register_hook].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HookId(u64);
+
+/// Register a hook to run before [exit] terminates the process, such as removing temporary
+/// files or restoring the terminal mode.
+///
+/// Hooks run in the reverse order of their registration. A panicking hook does not prevent the
+/// remaining hooks from running.
+/// # Examples
+/// ```no_run
+/// use uerr::UserError;
+///
+/// uerr::exit::register_hook(|| {
+///     let _ = std::fs::remove_file("output.tmp");
+/// });
+///
+/// UserError::from("could not finish writing").exit(1);
+/// ```
+pub fn register_hook<F>(hook: F) -> HookId
+where
+    F: FnOnce() + Send + 'static,
+{
+    let id = NEXT_HOOK.fetch_add(1, Ordering::Relaxed);
+
+    HOOKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push((id, Box::new(hook)));
+    HookId(id)
+}
+
+/// Unregister a hook registered with [register_hook].
+///
+/// Returns false if the hook was already unregistered or has run.
+pub fn unregister_hook(id: HookId) -> bool {
+    let mut hooks = HOOKS.lock().unwrap_or_else(PoisonError::into_inner);
+    let len = hooks.len();
+
+    hooks.retain(|(i, _)| *i != id.0);
+    hooks.len() != len
+}
+
+/// Run and unregister all hooks, most recently registered first, then flush stdout and stderr.
+pub fn run_hooks() {
+    // The lock is released before running the hooks, so they may register hooks or exit.
+    let hooks = std::mem::take(&mut *HOOKS.lock().unwrap_or_else(PoisonError::into_inner));
+
+    for (_, hook) in hooks.into_iter().rev() {
+        let _ = panic::catch_unwind(AssertUnwindSafe(hook));
+    }
+
+    let _ = io::stdout().flush();
+    let _ = io::stderr().flush();
+}
+
+/// Run the registered hooks and flush stdout and stderr, then exit the process.
+///
+/// Within [expect_exit](crate::testing::expect_exit), this unwinds instead.
+///
+/// See [run_hooks] and [std::process::exit(i32)].
+pub fn exit(code: impl Into<i32>) -> ! {
+    let code = code.into();
+
+    crate::testing::exit(code);
+    run_hooks();
+    std::process::exit(code);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{register_hook, run_hooks, unregister_hook, ExitCode, ExitCodeStrategy, FromError};
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn hooks_run_in_reverse() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let push = |n| {
+            let order = Arc::clone(&order);
+            move || order.lock().unwrap().push(n)
+        };
+
+        register_hook(push(1));
+        let removed = register_hook(push(2));
+        register_hook(|| panic!("hook panicked"));
+        register_hook(push(3));
+
+        assert!(unregister_hook(removed));
+        run_hooks();
+
+        assert_eq!(*order.lock().unwrap(), [3, 1]);
+        assert!(!unregister_hook(removed));
+    }
+
+    #[test]
+    fn strategies() {
+        let err = io::Error::from(io::ErrorKind::NotFound);
+
+        assert_eq!(7.exit_code(&err), 7);
+        assert_eq!(ExitCode::Config.exit_code(&err), 78);
+        assert_eq!(FromError.exit_code(&err), 66);
+        assert_eq!((|_: &io::Error| 9).exit_code(&err), 9);
+        assert_eq!(FromError.exit_code(&io::Error::other("x")), 74);
+    }
+}
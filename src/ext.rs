@@ -0,0 +1,211 @@
+//! Extension traits for converting [Result]s and [Option]s into [UserError]s inline.
+use crate::{IntoUserError, UserError};
+
+/// Attach a message, reasons and help to the error of a [Result].
+/// # Examples
+/// ```
+/// use std::fs;
+/// use uerr::{ResultExt, UserError};
+///
+/// fn load() -> Result<String, UserError> {
+///     fs::read_to_string("does/not/exist.toml")
+///         .user_err("could not open config")
+///         .with_help(|| "Create the file with `mytool init`.")
+/// }
+///
+/// let err = load().unwrap_err();
+///
+/// assert_eq!(err.message(), "could not open config");
+/// assert_eq!(err.reasons().len(), 1);
+/// assert_eq!(err.help(), &["Create the file with `mytool init`."]);
+/// ```
+pub trait ResultExt<T, E> {
+    /// Replace the error with a [UserError] with the given message.
+    ///
+    /// The original error is converted with [IntoUserError] and its message becomes the first
+    /// reason. A [UserError] is instead kept whole as a nested cause, along with its code,
+    /// reasons and help, and its [ExitCode](crate::exit::ExitCode) is carried over; see
+    /// [UserError::add_cause].
+    fn user_err(self, message: impl Into<String>) -> Result<T, UserError>
+    where
+        E: IntoUserError + 'static;
+
+    /// Add a reason, computed only on error, to the error converted with [IntoUserError].
+    ///
+    /// A [UserError] is kept whole rather than flattened into its message.
+    fn with_reason<F, S>(self, f: F) -> Result<T, UserError>
+    where
+        E: IntoUserError + 'static,
+        F: FnOnce() -> S,
+        S: Into<String>;
+
+    /// Add a help message, computed only on error, to the error converted with [IntoUserError].
+    ///
+    /// A [UserError] is kept whole rather than flattened into its message.
+    fn with_help<F, S>(self, f: F) -> Result<T, UserError>
+    where
+        E: IntoUserError + 'static,
+        F: FnOnce() -> S,
+        S: Into<String>;
+}
+
+// The methods match rather than using `map_err`, as closures do not propagate `#[track_caller]`.
+impl<T, E> ResultExt<T, E> for Result<T, E> {
+    #[track_caller]
+    fn user_err(self, message: impl Into<String>) -> Result<T, UserError>
+    where
+        E: IntoUserError + 'static,
+    {
+        match self {
+            Ok(v) => Ok(v),
+            Err(err) => {
+                let mut user_err = UserError::new(message.into());
+
+                match crate::downcast_user_err(err) {
+                    Ok(cause) => {
+                        if let Some(code) = cause.exit_code() {
+                            user_err.set_exit_code(code);
+                        }
+                        user_err.add_cause(cause);
+                    }
+                    Err(err) => user_err.reasons.push(err.into_user_err().message),
+                }
+                Err(user_err)
+            }
+        }
+    }
+
+    #[inline]
+    #[track_caller]
+    fn with_reason<F, S>(self, f: F) -> Result<T, UserError>
+    where
+        E: IntoUserError + 'static,
+        F: FnOnce() -> S,
+        S: Into<String>,
+    {
+        match self {
+            Ok(v) => Ok(v),
+            Err(err) => Err(crate::to_user_err(err).and_reason(f())),
+        }
+    }
+
+    #[inline]
+    #[track_caller]
+    fn with_help<F, S>(self, f: F) -> Result<T, UserError>
+    where
+        E: IntoUserError + 'static,
+        F: FnOnce() -> S,
+        S: Into<String>,
+    {
+        match self {
+            Ok(v) => Ok(v),
+            Err(err) => Err(crate::to_user_err(err).and_help(f())),
+        }
+    }
+}
+
+/// Convert an [Option] into a [Result] with a [UserError] describing the missing value.
+/// # Examples
+/// ```
+/// use uerr::{OptionExt, ResultExt};
+///
+/// let err = std::env::var_os("UERR_SURELY_UNSET")
+///     .user_err("UERR_SURELY_UNSET is not set")
+///     .with_help(|| "Set it to your API token.")
+///     .unwrap_err();
+///
+/// assert_eq!(err.message(), "UERR_SURELY_UNSET is not set");
+/// ```
+pub trait OptionExt<T> {
+    /// Convert [None] into a [UserError] with the given message.
+    fn user_err(self, message: impl Into<String>) -> Result<T, UserError>;
+}
+
+impl<T> OptionExt<T> for Option<T> {
+    #[inline]
+    #[track_caller]
+    fn user_err(self, message: impl Into<String>) -> Result<T, UserError> {
+        match self {
+            Some(v) => Ok(v),
+            None => Err(UserError::new(message.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ResultExt;
+    use crate::exit::ExitCode;
+    use crate::theme::Theme;
+    use crate::UserError;
+
+    #[test]
+    fn keeps_original_error() {
+        let res: Result<(), _> = "x".parse::<u8>().map(drop);
+        let err = res
+            .user_err("invalid port")
+            .with_reason(|| "ports are numbers")
+            .with_help(|| "Use a number between 0 and 255.")
+            .unwrap_err();
+
+        assert_eq!(err.message(), "invalid port");
+        assert_eq!(
+            err.reasons(),
+            &["invalid digit found in string", "ports are numbers"]
+        );
+        assert_eq!(err.help(), &["Use a number between 0 and 255."]);
+    }
+
+    #[test]
+    fn keeps_user_error() {
+        let res = Err::<(), _>(
+            UserError::from("could not parse a.toml")
+                .and_reason("expected `=`")
+                .and_help("See the manual.")
+                .and_code("E01")
+                .and_exit_code(ExitCode::DataErr),
+        );
+        let err = res.user_err("could not load config").unwrap_err();
+
+        assert!(err.reasons().is_empty());
+        assert!(err.help().is_empty());
+        assert_eq!(err.exit_code(), Some(ExitCode::DataErr));
+
+        let cause = &err.causes()[0];
+
+        assert_eq!(cause.message(), "could not parse a.toml");
+        assert_eq!(cause.code(), Some("E01"));
+        assert_eq!(cause.reasons(), &["expected `=`"]);
+        assert_eq!(cause.help(), &["See the manual."]);
+
+        let mut s = String::new();
+        err.fmt_with(&mut s, "error: ", &Theme::ASCII.without_styles())
+            .unwrap();
+
+        assert_eq!(
+            s,
+            "error: could not load config
+ - caused by: `- [E01] could not parse a.toml
+     |            - caused by: expected `=`
+     |            + help: See the manual.
+"
+        );
+    }
+
+    #[test]
+    fn with_help_on_any_error() {
+        let err = "x"
+            .parse::<u8>()
+            .with_help(|| "Pass a number.")
+            .unwrap_err();
+
+        assert_eq!(err.message(), "invalid digit found in string");
+        assert_eq!(err.help(), &["Pass a number."]);
+
+        let res = Err::<(), _>(UserError::from("bad config").and_code("E01"));
+        let err = res.with_reason(|| "line 3").unwrap_err();
+
+        assert_eq!(err.code(), Some("E01"));
+        assert_eq!(err.reasons(), &["line 3"]);
+    }
+}
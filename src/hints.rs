@@ -0,0 +1,197 @@
+//! Tailored reasons and help for each [io::ErrorKind].
+//!
+//! [UserError::from_io](crate::UserError::from_io), the `From<io::Error>` conversion and
+//! [unwrap_io](crate::unwrap_io) consult this table. Applications may replace the built-in
+//! [Hint] of a kind with [set_hint], or add to it with [extend_hint].
+use std::io::{self, ErrorKind};
+use std::sync::{PoisonError, RwLock};
+
+use crate::UserError;
+
+static TABLE: RwLock<Vec<Entry>> = RwLock::new(Vec::new());
+
+/// The hints set for a single [ErrorKind].
+struct Entry {
+    kind: ErrorKind,
+    replacement: Option<Hint>,
+    extensions: Vec<Hint>,
+}
+
+/// Reasons and help added to a [UserError] created from an [io::Error].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Hint {
+    reasons: Vec<String>,
+    help: Vec<String>,
+}
+
+impl Hint {
+    /// Create a new, empty Hint.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            reasons: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    /// Add a reason to this Hint.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn and_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reasons.push(reason.into());
+        self
+    }
+
+    /// Add a help message to this Hint.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn and_help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    #[inline]
+    pub fn reasons(&self) -> &[String] {
+        &self.reasons
+    }
+
+    #[inline]
+    pub fn help(&self) -> &[String] {
+        &self.help
+    }
+
+    fn apply_to(&self, user_err: &mut UserError) {
+        user_err.reasons.extend(self.reasons.iter().cloned());
+        user_err.help.extend(self.help.iter().cloned());
+    }
+}
+
+/// The built-in [Hint] of the given kind, if any.
+pub fn default_hint(kind: ErrorKind) -> Option<Hint> {
+    let hint = Hint::new();
+
+    let hint = match kind {
+        ErrorKind::NotFound => {
+            hint.and_help("Check that the path exists and is spelled correctly.")
+        }
+        ErrorKind::PermissionDenied => hint.and_help(
+            "Check the ownership and mode of the file, or run the program with sufficient \
+             privileges.",
+        ),
+        ErrorKind::AlreadyExists => {
+            hint.and_help("Remove the existing file, or choose a different path.")
+        }
+        ErrorKind::NotADirectory => {
+            hint.and_help("Check that each component of the path is a directory.")
+        }
+        ErrorKind::IsADirectory => {
+            hint.and_help("Provide the path of a file rather than a directory.")
+        }
+        ErrorKind::DirectoryNotEmpty => {
+            hint.and_help("Remove the contents of the directory first.")
+        }
+        ErrorKind::ReadOnlyFilesystem => hint
+            .and_reason("The filesystem is mounted read-only.")
+            .and_help("Choose a location on a writable filesystem."),
+        ErrorKind::StorageFull => hint.and_help("Free some disk space and try again."),
+        ErrorKind::AddrInUse => hint
+            .and_reason("Another process may already be listening on this address.")
+            .and_help("Stop that process, or choose a different port."),
+        ErrorKind::AddrNotAvailable => hint
+            .and_reason("The address is not assigned to this machine.")
+            .and_help("Check the address, or bind to `0.0.0.0` to listen on every interface."),
+        ErrorKind::ConnectionRefused => hint
+            .and_reason("Nothing is listening at the address.")
+            .and_help("Check that the server is running and accepting connections."),
+        ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted => hint
+            .and_reason("The remote host closed the connection.")
+            .and_help("Try again."),
+        ErrorKind::NotConnected => hint.and_help("Establish the connection before using it."),
+        ErrorKind::TimedOut => hint
+            .and_reason("The remote host did not respond in time.")
+            .and_help("Check your network connection and try again."),
+        ErrorKind::BrokenPipe => hint
+            .and_reason("The reading end of the pipe was closed before all data was written.")
+            .and_help("This is expected when the output is piped into a program such as `head`."),
+        ErrorKind::InvalidData => hint
+            .and_reason("The data is not valid, such as text which is not UTF-8.")
+            .and_help("Check that the input is in the expected format."),
+        ErrorKind::UnexpectedEof => hint
+            .and_reason("The input ended before all of the expected data was read.")
+            .and_help("Check that the input is complete and not truncated."),
+        ErrorKind::Interrupted | ErrorKind::WouldBlock => {
+            hint.and_help("The operation may succeed if retried.")
+        }
+        ErrorKind::OutOfMemory => {
+            hint.and_help("Close other programs to free memory and try again.")
+        }
+        ErrorKind::Unsupported => {
+            hint.and_reason("This operation is not supported on this platform.")
+        }
+        _ => return None,
+    };
+    Some(hint)
+}
+
+/// Run the given function with the [Entry] of the given kind, creating it if needed.
+fn with_entry<F>(kind: ErrorKind, f: F)
+where
+    F: FnOnce(&mut Entry),
+{
+    let mut table = TABLE.write().unwrap_or_else(PoisonError::into_inner);
+
+    let index = match table.iter().position(|entry| entry.kind == kind) {
+        Some(index) => index,
+        None => {
+            table.push(Entry {
+                kind,
+                replacement: None,
+                extensions: Vec::new(),
+            });
+            table.len() - 1
+        }
+    };
+    f(&mut table[index]);
+}
+
+/// Replace the built-in [Hint] of the given kind, or the Hint set by a previous call.
+///
+/// Hints added with [extend_hint] are kept.
+pub fn set_hint(kind: ErrorKind, hint: Hint) {
+    with_entry(kind, |entry| entry.replacement = Some(hint));
+}
+
+/// Add the given [Hint] to those of the given kind.
+pub fn extend_hint(kind: ErrorKind, hint: Hint) {
+    with_entry(kind, |entry| entry.extensions.push(hint));
+}
+
+/// Remove all replaced and extended hints, restoring the built-in table.
+pub fn reset_hints() {
+    TABLE
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clear();
+}
+
+/// Add the hints of the kind of the given error to the given [UserError].
+pub(crate) fn apply(err: &io::Error, user_err: &mut UserError) {
+    let kind = err.kind();
+    let table = TABLE.read().unwrap_or_else(PoisonError::into_inner);
+    let entry = table.iter().find(|entry| entry.kind == kind);
+
+    match entry.and_then(|entry| entry.replacement.as_ref()) {
+        Some(hint) => hint.apply_to(user_err),
+        None => {
+            if let Some(hint) = default_hint(kind) {
+                hint.apply_to(user_err);
+            }
+        }
+    }
+
+    for hint in entry.iter().flat_map(|entry| &entry.extensions) {
+        hint.apply_to(user_err);
+    }
+}
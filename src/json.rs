@@ -0,0 +1,162 @@
+//! Machine-readable JSON output, enabled by the `serde` feature.
+//!
+//! [UserError] implements [Serialize], and [set_output_mode] switches
+//! [UserError::print_all] to emitting one JSON object per line, which suits tools implementing a
+//! `--message-format=json` flag.
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::severity::Severity;
+use crate::snippet::{Label, Snippet};
+use crate::UserError;
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// The format in which [UserError::print_all] renders errors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OutputMode {
+    /// Human-readable text. This is the default.
+    #[default]
+    Human,
+    /// One JSON object per line.
+    Json,
+}
+
+/// Set the global [OutputMode].
+#[inline]
+pub fn set_output_mode(mode: OutputMode) {
+    JSON.store(mode == OutputMode::Json, Ordering::Relaxed);
+}
+
+/// Get the global [OutputMode]. Defaults to [OutputMode::Human].
+#[inline]
+pub fn output_mode() -> OutputMode {
+    if JSON.load(Ordering::Relaxed) {
+        OutputMode::Json
+    } else {
+        OutputMode::Human
+    }
+}
+
+impl UserError {
+    /// Serialize this UserError as a single line of JSON.
+    /// # Examples
+    /// ```
+    /// use uerr::UserError;
+    ///
+    /// let json = UserError::from("could not open file")
+    ///     .and_code("E0042")
+    ///     .and_help("Does this file exist?")
+    ///     .to_json();
+    ///
+    /// assert_eq!(
+    ///     json,
+    ///     r#"{"severity":"error","code":"E0042","message":"could not open file","reasons":[],"help":["Does this file exist?"],"spans":[]}"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("UserError serialization is infallible")
+    }
+}
+
+impl Serialize for Severity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.label())
+    }
+}
+
+impl Serialize for UserError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("severity", self.severity())?;
+        map.serialize_entry("code", &self.code())?;
+        map.serialize_entry("message", self.message())?;
+        map.serialize_entry("reasons", self.reasons())?;
+        map.serialize_entry("help", self.help())?;
+        map.serialize_entry("spans", &Spans(self.snippet()))?;
+
+        if !self.causes().is_empty() {
+            map.serialize_entry("causes", self.causes())?;
+        }
+
+        if let Some(source) = std::error::Error::source(self) {
+            map.serialize_entry("source", &source.to_string())?;
+        }
+        map.end()
+    }
+}
+
+struct Spans<'a>(Option<&'a Snippet>);
+
+impl Serialize for Spans<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let labels = self.0.map_or(&[][..], Snippet::labels);
+        let mut seq = serializer.serialize_seq(Some(labels.len()))?;
+
+        if let Some(snippet) = self.0 {
+            for label in labels {
+                seq.serialize_element(&Span { snippet, label })?;
+            }
+        }
+        seq.end()
+    }
+}
+
+struct Span<'a> {
+    snippet: &'a Snippet,
+    label: &'a Label,
+}
+
+impl Serialize for Span<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let range = self.label.range();
+        let (line_start, column_start) = self.snippet.position(range.start);
+        let (line_end, column_end) = self.snippet.position(range.end);
+
+        let mut map = serializer.serialize_map(Some(9))?;
+        map.serialize_entry("file", self.snippet.name())?;
+        map.serialize_entry("byte_start", &range.start)?;
+        map.serialize_entry("byte_end", &range.end)?;
+        map.serialize_entry("line_start", &line_start)?;
+        map.serialize_entry("column_start", &column_start)?;
+        map.serialize_entry("line_end", &line_end)?;
+        map.serialize_entry("column_end", &column_end)?;
+        map.serialize_entry("label", self.label.message())?;
+        map.serialize_entry("primary", &self.label.is_primary())?;
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::snippet::{Label, Snippet};
+    use crate::UserError;
+
+    #[test]
+    fn spans() {
+        let json = UserError::warning("unused key")
+            .and_snippet(
+                Snippet::new("a.toml", "x = 1\ncolour = 2\n")
+                    .and_label(Label::primary(6..12, "unknown")),
+            )
+            .to_json();
+
+        assert_eq!(
+            json,
+            r#"{"severity":"warning","code":null,"message":"unused key","reasons":[],"help":[],"spans":[{"file":"a.toml","byte_start":6,"byte_end":12,"line_start":2,"column_start":1,"line_end":2,"column_end":7,"label":"unknown","primary":true}]}"#
+        );
+    }
+}
@@ -1,8 +1,89 @@
-use std::fmt::Display;
+use std::any::Any;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::io;
+use std::panic::Location;
+
+use severity::Severity;
+use snippet::Snippet;
+use style::Styles;
+use theme::Theme;
+
+pub use diagnostics::Diagnostics;
+use exit::{ExitCode, ExitCodeStrategy};
+pub use ext::{OptionExt, ResultExt};
+pub use panic::install_panic_hook;
+pub use report::Report;
+
+#[doc(hidden)]
+pub use macros::__private;
+
+/// Derive `From<T> for UserError` for an error type, with the `derive` feature.
+/// # Examples
+/// ```
+/// use std::io;
+/// use uerr::exit::ExitCode;
+/// use uerr::UserError;
+///
+/// #[derive(Debug, UserError)]
+/// #[uerr(help = "See `mytool --help`.")]
+/// enum ConfigError {
+///     #[uerr(message = "could not read {path}", code = "E01", exit = 66)]
+///     Read {
+///         path: String,
+///         #[uerr(source)]
+///         source: io::Error,
+///     },
+///     #[uerr(message = "unknown key `{0}`", help = "Did you mean `{1}`?")]
+///     UnknownKey(String, &'static str),
+/// }
+///
+/// let err: UserError = ConfigError::Read {
+///     path: "a.toml".into(),
+///     source: io::Error::new(io::ErrorKind::NotFound, "not found"),
+/// }
+/// .into();
+///
+/// assert_eq!(err.message(), "could not read a.toml");
+/// assert_eq!(err.reasons(), &["not found"]);
+/// assert_eq!(err.code(), Some("E01"));
+/// assert_eq!(err.exit_code(), Some(ExitCode::NoInput));
+///
+/// let err: UserError = ConfigError::UnknownKey("colour".into(), "color").into();
+///
+/// assert_eq!(err.message(), "unknown key `colour`");
+/// assert_eq!(err.help(), &["See `mytool --help`.", "Did you mean `color`?"]);
+/// ```
+///
+/// See the `uerr-derive` crate for the supported attributes.
+#[cfg(feature = "derive")]
+pub use uerr_derive::UserError;
+
+mod backtrace;
+pub mod code;
+mod diagnostics;
+pub mod exit;
+mod ext;
+pub mod hints;
+#[cfg(feature = "serde")]
+pub mod json;
+mod macros;
+pub mod panic;
+mod report;
+pub mod severity;
+pub mod snippet;
+pub mod style;
+pub mod testing;
+pub mod theme;
+mod width;
 
 /// Unwrap the value contained within the given [Result] and return it, else print the contained
 /// [std::io::Error] and exit the process.
 ///
+/// Reasons and help tailored to the [io::ErrorKind] are added; see [hints]. The process exits
+/// with the [ExitCode] of the [io::ErrorKind].
+///
 /// See [std::process::exit(i32)].
 pub fn unwrap_io<T>(msg: &str, res: std::io::Result<T>) -> T {
     let err = match res {
@@ -10,15 +91,70 @@ pub fn unwrap_io<T>(msg: &str, res: std::io::Result<T>) -> T {
         Err(err) => err,
     };
 
-    let code = err.raw_os_error().unwrap_or_else(|| {
-        eprintln!("note: the error code could not be found for this variant; reverting to -1...");
-        -1
-    });
-    err.into_user_err().print_all(msg).exit(code);
+    UserError::from_io(&err)
+        .print_all(msg)
+        .exit(ExitCode::from(err.kind()));
 }
 
-/// A human-readable error interface.
+/// Unwrap the value contained within the given [Result] and return it, else print the contained
+/// error and exit the process with the code decided by the given [ExitCodeStrategy].
+/// # Examples
+/// ```no_run
+/// use uerr::exit::{ExitCode, FromError};
+///
+/// // Exit with a fixed code.
+/// let port: u16 = uerr::unwrap_or_exit("error: ", "80".parse(), 2);
+/// let port: u16 = uerr::unwrap_or_exit("error: ", "80".parse(), ExitCode::Usage);
+///
+/// // Exit with a code derived from the error.
+/// let file = uerr::unwrap_or_exit("error: ", std::fs::read("a.txt"), FromError);
+///
+/// // Exit with a code mapped from the error.
+/// let n: u8 = uerr::unwrap_or_exit("error: ", "300".parse(), |err: &std::num::ParseIntError| {
+///     if *err.kind() == std::num::IntErrorKind::PosOverflow { 3 } else { 2 }
+/// });
+/// ```
+///
+/// A [UserError] is printed as is, keeping its code, reasons and help, and [exit::FromError]
+/// exits with its stored [ExitCode].
+///
+/// See [std::process::exit(i32)].
+pub fn unwrap_or_exit<T, E, S>(msg: &str, res: Result<T, E>, strategy: S) -> T
+where
+    E: IntoUserError + 'static,
+    S: ExitCodeStrategy<E>,
+{
+    let err = match res {
+        Ok(v) => return v,
+        Err(err) => err,
+    };
+
+    let code = strategy.exit_code(&err);
+
+    to_user_err(err).print_all(msg).exit(code);
+}
+
+/// Unwrap the value contained within the given [Option] and return it, else print the given
+/// message and exit the process with the given code, which may be an [i32] or an [ExitCode].
+/// # Examples
+/// ```no_run
+/// use uerr::exit::ExitCode;
+///
+/// let path = uerr::expect_or_exit("error: ", std::env::args().nth(1), "no path given", 2);
+/// let path =
+///     uerr::expect_or_exit("error: ", std::env::args().nth(1), "no path given", ExitCode::Usage);
 /// ```
+///
+/// See [std::process::exit(i32)].
+pub fn expect_or_exit<T>(msg: &str, opt: Option<T>, message: &str, code: impl Into<i32>) -> T {
+    match opt {
+        Some(v) => v,
+        None => UserError::from(message).print_all(msg).exit(code),
+    }
+}
+
+/// A human-readable error interface.
+/// ```no_run
 /// use uerr::UserError;
 ///
 /// UserError::from("could not open file")
@@ -27,47 +163,450 @@ pub fn unwrap_io<T>(msg: &str, res: std::io::Result<T>) -> T {
 ///     .print_all("uerr/error: ")
 ///     .exit(1);
 /// ```
+///
+/// UserError implements [Error], so it may be returned with `?` from any function returning
+/// `Result<T, Box<dyn Error>>`. Its [Display] implementation produces the same layout as
+/// [UserError::print_all] with an empty prefix.
 #[derive(Default)]
 pub struct UserError {
     message: String,
     reasons: Vec<String>,
     help: Vec<String>,
+    details: Box<Details>,
+}
+
+/// The less frequently used parts of a [UserError], boxed to keep `Result<T, UserError>` small.
+#[derive(Default)]
+struct Details {
+    severity: Severity,
+    exit_code: Option<ExitCode>,
+    code: Option<String>,
+    snippet: Option<Snippet>,
+    source: Option<Box<dyn Error + Send + Sync + 'static>>,
+    backtrace: Option<Backtrace>,
+    location: Option<&'static Location<'static>>,
+    causes: Vec<UserError>,
+}
+
+/// Bridges a [fmt::Write] based renderer onto an [io::Write] sink, retaining the underlying
+/// [io::Error] which [fmt::Error] is unable to carry.
+struct IoAdapter<'a, W: ?Sized> {
+    inner: &'a mut W,
+    error: Option<io::Error>,
+}
+
+impl<W> fmt::Write for IoAdapter<'_, W>
+where
+    W: io::Write + ?Sized,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+impl<'a, W> IoAdapter<'a, W>
+where
+    W: io::Write + ?Sized,
+{
+    /// Run the given renderer against the sink, converting the result into an [io::Result].
+    fn run<F>(inner: &'a mut W, render: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut Self) -> fmt::Result,
+    {
+        let mut adapter = Self { inner, error: None };
+
+        render(&mut adapter).map_err(|_| {
+            adapter
+                .error
+                .take()
+                .unwrap_or_else(|| io::Error::other("formatter error"))
+        })
+    }
 }
 
 impl UserError {
-    fn enumerator<'a, I>(&self, i: I, first: &str, rest: &str)
+    fn enumerator<'a, W, I>(
+        w: &mut W,
+        i: I,
+        style: style::Style,
+        (first, rest): (&str, &str),
+        theme: &Theme,
+        width: Option<usize>,
+    ) -> fmt::Result
     where
+        W: fmt::Write + ?Sized,
         I: IntoIterator<Item = &'a String>,
     {
-        let mut it = i.into_iter();
+        let mut leader = first;
+
+        for f in i {
+            // Embedded and wrapped lines continue under the text column, after the continuation
+            // leader.
+            for line in theme.lines(f, width::str_width(leader), width) {
+                writeln!(w, "{}{line}", style.paint(theme.blank_leader(leader, line)))?;
+                leader = rest;
+            }
+        }
+        Ok(())
+    }
+
+    /// Render this UserError, wrapping it at the narrower of [Theme::max_width] and the given
+    /// terminal width, and followed by its [Backtrace] if `backtrace` is set.
+    fn render<W>(
+        &self,
+        w: &mut W,
+        prefix: &dyn Display,
+        theme: &Theme,
+        colored: bool,
+        terminal_width: Option<usize>,
+        backtrace: bool,
+    ) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        let styles = if colored {
+            &theme.styles
+        } else {
+            &Styles::PLAIN
+        };
+
+        let width = match (theme.max_width, terminal_width) {
+            (Some(max), Some(terminal)) => Some(max.min(terminal)),
+            (max, terminal) => max.or(terminal),
+        };
+
+        self.render_at(w, prefix, theme, styles, width, 0)?;
+
+        match &self.details.backtrace {
+            Some(trace) if backtrace => backtrace::render(w, trace, theme, styles),
+            _ => Ok(()),
+        }
+    }
+
+    /// Render the causes of this UserError, at the given depth of the tree, as connected lines.
+    fn cause_tree(
+        &self,
+        theme: &Theme,
+        styles: &Styles,
+        width: Option<usize>,
+        depth: usize,
+    ) -> Result<Vec<String>, fmt::Error> {
+        let causes = &self.details.causes;
+
+        let shown = if theme.max_cause_depth.is_some_and(|max| depth >= max) {
+            0
+        } else {
+            theme
+                .max_causes
+                .map_or(causes.len(), |max| max.min(causes.len()))
+        };
+        let hidden = causes.len() - shown;
+
+        let last_pipe = " ".repeat(width::str_width(&theme.tree_last));
+        let mut lines = Vec::new();
+
+        for (i, cause) in causes[..shown].iter().enumerate() {
+            let (branch, pipe) = if i + 1 == shown && hidden == 0 {
+                (&*theme.tree_last, last_pipe.as_str())
+            } else {
+                (&*theme.tree_branch, &*theme.tree_pipe)
+            };
+
+            let mut s = String::new();
+            let width = width.map(|width| width.saturating_sub(width::str_width(branch)));
+            cause.render_at(&mut s, &"", theme, styles, width, depth + 1)?;
+
+            let mut connector = branch;
+
+            for line in s.lines() {
+                let shown = theme.blank_leader(connector, line);
+                lines.push(format!("{}{line}", styles.reason.paint(shown)));
+                connector = pipe;
+            }
+        }
+
+        if hidden > 0 {
+            lines.push(format!(
+                "{}... and {hidden} more",
+                styles.reason.paint(&theme.tree_last)
+            ));
+        }
+        Ok(lines)
+    }
+
+    /// Render this UserError at the given depth of a tree of causes.
+    fn render_at<W>(
+        &self,
+        w: &mut W,
+        prefix: &dyn Display,
+        theme: &Theme,
+        styles: &Styles,
+        width: Option<usize>,
+        depth: usize,
+    ) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        let style = self.details.severity.style(styles);
+        let prefix = prefix.to_string();
+
+        let head = match &self.details.code {
+            // The code is placed before the trailing colon of the prefix, as in `error[E01]: `.
+            Some(code) => match prefix.trim_end().strip_suffix(':') {
+                Some(head) => format!("{head}[{code}]:{}", &prefix[head.len() + 1..]),
+                None => format!("{prefix}[{code}] "),
+            },
+            None => prefix,
+        };
+
+        let head_width = width::str_width(&head);
+        let pad = " ".repeat(head_width);
+        let mut leader = head.as_str();
+
+        for line in theme.lines(&self.message, head_width, width) {
+            let shown = theme.blank_leader(leader, line);
+            writeln!(w, "{}", style.paint(format_args!("{shown}{line}")))?;
+            leader = &pad;
+        }
+
+        if let Some(location) = self.details.location.filter(|_| theme.show_location) {
+            writeln!(
+                w,
+                "{}{}",
+                " ".repeat(theme.indent),
+                styles.gutter.paint(format_args!("at {location}"))
+            )?;
+        }
+
+        if let Some(snippet) = &self.details.snippet {
+            let gutter = if theme.gutter.is_empty() {
+                "|"
+            } else {
+                &theme.gutter
+            };
+
+            snippet.render(w, gutter, styles, self.details.severity.style(styles))?;
+        }
+
+        let (first, rest) = theme.leaders(&theme.reason_bullet, &theme.reason_label);
+        Self::enumerator(
+            w,
+            &self.reasons,
+            styles.reason,
+            (&first, &rest),
+            theme,
+            width,
+        )?;
+
+        if !self.details.causes.is_empty() {
+            // The causes continue the reasons section, under its text column.
+            let mut leader = if self.reasons.is_empty() {
+                &first
+            } else {
+                &rest
+            };
+            let width = width.map(|width| width.saturating_sub(width::str_width(&rest)));
+
+            for line in self.cause_tree(theme, styles, width, depth)? {
+                writeln!(w, "{}{line}", styles.reason.paint(leader))?;
+                leader = &rest;
+            }
+        }
+
+        let (first, rest) = theme.leaders(&theme.help_bullet, &theme.help_label);
+        Self::enumerator(w, &self.help, styles.help, (&first, &rest), theme, width)
+    }
+
+    /// Render this UserError into the given [fmt::Write] sink, starting with the given prefix.
+    ///
+    /// This produces the same output as [UserError::print_all] without colors, including the
+    /// captured [Backtrace], if any.
+    pub fn fmt_to<W, D>(&self, w: &mut W, prefix: D) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+        D: Display,
+    {
+        self.render(w, &prefix, &theme::theme(), false, None, true)
+    }
+
+    /// Render this UserError into the given [fmt::Write] sink using the given [Theme],
+    /// including its styles and the captured [Backtrace], if any.
+    pub fn fmt_with<W, D>(&self, w: &mut W, prefix: D, theme: &Theme) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+        D: Display,
+    {
+        self.render(w, &prefix, theme, true, None, true)
+    }
+
+    /// Render this UserError into the given [io::Write] sink, starting with the given prefix.
+    ///
+    /// The captured [Backtrace], if any, is rendered after the help. Any error returned by the
+    /// sink is propagated.
+    pub fn write_to<W, D>(&self, w: &mut W, prefix: D) -> io::Result<()>
+    where
+        W: io::Write + ?Sized,
+        D: Display,
+    {
+        let theme = theme::theme();
+        IoAdapter::run(w, |a| self.render(a, &prefix, &theme, false, None, true))
+    }
+
+    /// Render this UserError into the given [io::Write] sink using the given [Theme],
+    /// including its styles and the captured [Backtrace], if any.
+    ///
+    /// Any error returned by the sink is propagated.
+    pub fn write_with<W, D>(&self, w: &mut W, prefix: D, theme: &Theme) -> io::Result<()>
+    where
+        W: io::Write + ?Sized,
+        D: Display,
+    {
+        IoAdapter::run(w, |a| self.render(a, &prefix, theme, true, None, true))
+    }
+
+    /// Render this UserError to stderr using the given [Theme].
+    ///
+    /// Colors are emitted according to the global [style::ColorChoice], and lines are wrapped at
+    /// the width of the terminal, which `COLUMNS` overrides; see [Theme::max_width]. The
+    /// captured [Backtrace], if any, is rendered after the help; see [UserError::backtrace].
+    /// Unlike [UserError::print_all_with], errors writing to stderr are returned to the caller.
+    ///
+    /// With the `serde` feature, a single line of JSON is written instead when the global
+    /// `json::OutputMode` is `Json`.
+    pub fn try_print_all_with<D>(&self, prefix: D, theme: &Theme) -> io::Result<&Self>
+    where
+        D: Display,
+    {
+        #[cfg(feature = "serde")]
+        if json::output_mode() == json::OutputMode::Json {
+            use std::io::Write;
 
-        if let Some(f) = it.next() {
-            eprintln!("{first}{f}");
+            if !testing::capture(|s| {
+                s.push_str(&self.to_json());
+                s.push('\n');
+            }) {
+                writeln!(io::stderr().lock(), "{}", self.to_json())?;
+            }
+            return Ok(self);
         }
 
-        for f in it {
-            eprintln!("{rest}{f}");
+        if testing::capture(|s| {
+            let _ = self.render(s, &prefix, theme, false, None, true);
+        }) {
+            return Ok(self);
         }
+
+        let colored = style::color_choice().enabled_for_stderr();
+
+        IoAdapter::run(&mut io::stderr().lock(), |a| {
+            self.render(a, &prefix, theme, colored, width::terminal_width(), true)
+        })?;
+        Ok(self)
+    }
+
+    /// Render this UserError to stderr, starting with the given prefix.
+    ///
+    /// The global [Theme] is used, and colors are emitted according to the global
+    /// [style::ColorChoice].
+    /// Unlike [UserError::print_all], errors writing to stderr are returned to the caller.
+    pub fn try_print_all<D>(&self, prefix: D) -> io::Result<&Self>
+    where
+        D: Display,
+    {
+        self.try_print_all_with(prefix, &theme::theme())
+    }
+
+    /// Print the given prefix followed by the contained error message, using the given [Theme].
+    ///
+    /// Errors writing to stderr are ignored; see [UserError::try_print_all_with].
+    pub fn print_all_with<D>(&self, prefix: D, theme: &Theme) -> &Self
+    where
+        D: Display,
+    {
+        let _ = self.try_print_all_with(prefix, theme);
+        self
     }
 
     /// Exit the process.
     ///
-    /// See [std::process::exit(i32)].
+    /// The code may be an [i32] or an [ExitCode]. The hooks registered with
+    /// [exit::register_hook] run first, and stdout and stderr are flushed.
+    ///
+    /// Only a fatal [Severity] may exit; see [Severity::is_fatal]. Use
+    /// [UserError::exit_if_fatal] to let the Severity decide, or [exit::exit] to exit after a
+    /// warning regardless.
+    /// # Panics
+    /// Panics if the [Severity] of this UserError is not fatal.
+    #[inline]
+    #[track_caller]
+    pub fn exit(&self, code: impl Into<i32>) -> ! {
+        assert!(
+            self.details.severity.is_fatal(),
+            "a UserError with severity `{}` may not exit the process",
+            self.details.severity
+        );
+        exit::exit(code);
+    }
+
+    /// Exit the process with the stored [ExitCode], or [ExitCode::Failure] if there is none.
+    ///
+    /// See [UserError::exit].
+    #[inline]
+    #[track_caller]
+    pub fn terminate(&self) -> ! {
+        self.exit(self.exit_code_or_default());
+    }
+
+    /// Exit the process if the [Severity] of this UserError is fatal.
+    ///
+    /// Returns the current instance otherwise. See [Severity::is_fatal].
     #[inline]
-    pub fn exit(&self, code: i32) -> ! {
-        std::process::exit(code);
+    pub fn exit_if_fatal(&self, code: impl Into<i32>) -> &Self {
+        if self.details.severity.is_fatal() {
+            self.exit(code);
+        }
+        self
+    }
+
+    /// Render this UserError to stderr, prefixed with the label of its [Severity].
+    ///
+    /// Unlike [UserError::print], errors writing to stderr are returned to the caller.
+    pub fn try_print(&self) -> io::Result<&Self> {
+        self.try_print_all(format_args!("{}: ", self.details.severity))
+    }
+
+    /// Print this UserError to stderr, prefixed with the label of its [Severity].
+    /// ```no_run
+    /// use uerr::UserError;
+    ///
+    /// UserError::warning("unused configuration key `colour`")
+    ///     .and_help("Did you mean `color`?")
+    ///     .print();
+    /// ```
+    /// ```text
+    /// warning: unused configuration key `colour`
+    ///  + help: Did you mean `color`?
+    /// ```
+    ///
+    /// Errors writing to stderr are ignored; see [UserError::try_print].
+    pub fn print(&self) -> &Self {
+        let _ = self.try_print();
+        self
     }
 
     /// Print the given prefix followed by the contained error message.
     ///
     /// No padding is inserted between either elements.
+    ///
+    /// Errors writing to stderr are ignored; see [UserError::try_print_all].
     pub fn print_all<D>(&self, prefix: D) -> &Self
     where
         D: Display,
     {
-        eprintln!("{prefix}{}", self.message);
-        self.enumerator(&self.reasons, " - caused by: ", "     |        ");
-        self.enumerator(&self.help, " + help: ", "     |   ");
+        let _ = self.try_print_all(prefix);
         self
     }
 
@@ -101,18 +640,248 @@ impl UserError {
         self
     }
 
-    /// Create a new UserError.
+    /// Add a nested cause to this UserError, rendered with its own reasons, help and causes as a
+    /// tree beneath the reasons.
+    /// # Examples
+    /// ```
+    /// use uerr::theme::Theme;
+    /// use uerr::UserError;
+    ///
+    /// let err = UserError::from("deployment failed")
+    ///     .and_reason("2 of 3 hosts failed")
+    ///     .and_cause(UserError::from("web-1: connection refused").and_help("Is sshd running?"))
+    ///     .and_cause(UserError::from("web-2: disk full"));
+    ///
+    /// let mut s = String::new();
+    /// err.fmt_with(&mut s, "error: ", &Theme::UNICODE.without_styles()).unwrap();
+    ///
+    /// assert_eq!(
+    ///     s,
+    ///     "\
+    /// error: deployment failed
+    ///  ╰─▶ caused by: 2 of 3 hosts failed
+    ///      │          ├─ web-1: connection refused
+    ///      │          │   ╰─▶ help: Is sshd running?
+    ///      │          └─ web-2: disk full
+    /// "
+    /// );
+    /// ```
+    #[inline]
+    pub fn add_cause(&mut self, cause: impl Into<UserError>) {
+        self.details.causes.push(cause.into());
+    }
+
+    /// Add a nested cause to this UserError.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn and_cause(mut self, cause: impl Into<UserError>) -> Self {
+        self.details.causes.push(cause.into());
+        self
+    }
+
+    /// Set the underlying cause of this UserError, exposed through [Error::source].
+    #[inline]
+    pub fn set_source(&mut self, source: impl Into<Box<dyn Error + Send + Sync + 'static>>) {
+        self.details.source = Some(source.into());
+    }
+
+    /// Set the underlying cause of this UserError, exposed through [Error::source].
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn and_source(mut self, source: impl Into<Box<dyn Error + Send + Sync + 'static>>) -> Self {
+        self.details.source = Some(source.into());
+        self
+    }
+
+    /// Set the [Severity] of this UserError.
+    #[inline]
+    pub fn set_severity(&mut self, severity: Severity) {
+        self.details.severity = severity;
+    }
+
+    /// Set the [Severity] of this UserError.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn and_severity(mut self, severity: Severity) -> Self {
+        self.details.severity = severity;
+        self
+    }
+
+    /// Set the [ExitCode] associated with this UserError.
+    #[inline]
+    pub fn set_exit_code(&mut self, code: impl Into<ExitCode>) {
+        self.details.exit_code = Some(code.into());
+    }
+
+    /// Set the [ExitCode] associated with this UserError.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn and_exit_code(mut self, code: impl Into<ExitCode>) -> Self {
+        self.details.exit_code = Some(code.into());
+        self
+    }
+
+    /// Set the error code of this UserError, such as `E0042`.
+    ///
+    /// The code is rendered within the prefix, as in `error[E0042]: `. See [code].
     #[inline]
+    pub fn set_code(&mut self, code: impl Into<String>) {
+        self.details.code = Some(code.into());
+    }
+
+    /// Set the error code of this UserError, such as `E0042`.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn and_code(mut self, code: impl Into<String>) -> Self {
+        self.details.code = Some(code.into());
+        self
+    }
+
+    /// Attach a source [Snippet], rendered beneath the message.
+    #[inline]
+    pub fn set_snippet(&mut self, snippet: Snippet) {
+        self.details.snippet = Some(snippet);
+    }
+
+    /// Attach a source [Snippet], rendered beneath the message.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn and_snippet(mut self, snippet: Snippet) -> Self {
+        self.details.snippet = Some(snippet);
+        self
+    }
+
+    /// Attach a [Backtrace], rendered after the help by every renderer but [Display].
+    ///
+    /// Backtraces which were not captured are discarded.
+    #[inline]
+    pub fn set_backtrace(&mut self, backtrace: Backtrace) {
+        self.details.backtrace =
+            Some(backtrace).filter(|b| b.status() == BacktraceStatus::Captured);
+    }
+
+    /// Attach a [Backtrace], rendered after the help by every renderer but [Display].
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn and_backtrace(mut self, backtrace: Backtrace) -> Self {
+        self.set_backtrace(backtrace);
+        self
+    }
+
+    /// Create a new UserError with [Severity::Warning].
+    #[inline]
+    #[track_caller]
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(message.into()).and_severity(Severity::Warning)
+    }
+
+    /// Create a new UserError with [Severity::Info].
+    #[inline]
+    #[track_caller]
+    pub fn info(message: impl Into<String>) -> Self {
+        Self::new(message.into()).and_severity(Severity::Info)
+    }
+
+    /// Create a new UserError with [Severity::Note].
+    #[inline]
+    #[track_caller]
+    pub fn note(message: impl Into<String>) -> Self {
+        Self::new(message.into()).and_severity(Severity::Note)
+    }
+
+    /// Create a new UserError.
+    ///
+    /// A [Backtrace] is captured if enabled by the `RUST_LIB_BACKTRACE` or `RUST_BACKTRACE`
+    /// environment variables; see [Backtrace::capture]. The location of the caller is recorded;
+    /// see [UserError::location].
+    #[track_caller]
     pub fn new(message: String) -> Self {
-        Self {
+        let mut user_err = Self::without_backtrace(message);
+        user_err.set_backtrace(Backtrace::capture());
+        user_err
+    }
+
+    /// Create a new UserError without capturing a [Backtrace], for reports which do not stand
+    /// for a failure at the caller, such as a summary.
+    #[track_caller]
+    pub(crate) fn without_backtrace(message: String) -> Self {
+        let mut user_err = Self {
             message,
             reasons: Vec::new(),
             help: Vec::new(),
+            details: Box::default(),
+        };
+        user_err.details.location = Some(Location::caller());
+        user_err
+    }
+
+    /// Create a new UserError from the given [Error], using its [Display] text as the message.
+    ///
+    /// The [Error::source] chain is walked to fill the reasons, skipping any cause whose message
+    /// is already contained within the message of the error it caused.
+    /// # Examples
+    /// ```
+    /// use uerr::UserError;
+    ///
+    /// let err = "x".parse::<u8>().unwrap_err();
+    /// let user_err = UserError::from_error(&err);
+    ///
+    /// assert_eq!(user_err.message(), "invalid digit found in string");
+    /// ```
+    #[track_caller]
+    pub fn from_error(err: &dyn Error) -> Self {
+        let mut user_err = Self::new(err.to_string());
+        let mut parent = user_err.message.clone();
+        let mut cause = err.source();
+
+        while let Some(err) = cause {
+            let msg = err.to_string();
+
+            if !parent.contains(&msg) {
+                user_err.reasons.push(msg.clone());
+            }
+
+            parent = msg;
+            cause = err.source();
         }
+        user_err
+    }
+
+    /// Create a new UserError from the given [io::Error].
+    ///
+    /// Like [UserError::from_error], with reasons and help tailored to the [io::ErrorKind]
+    /// added afterwards, and the [ExitCode] of the [io::ErrorKind]. See [hints].
+    /// # Examples
+    /// ```
+    /// use std::io;
+    /// use uerr::UserError;
+    ///
+    /// let err = io::Error::new(io::ErrorKind::AddrInUse, "address in use");
+    /// let user_err = UserError::from_io(&err);
+    ///
+    /// assert_eq!(
+    ///     user_err.reasons(),
+    ///     &["Another process may already be listening on this address."]
+    /// );
+    /// assert_eq!(user_err.help(), &["Stop that process, or choose a different port."]);
+    /// ```
+    #[track_caller]
+    pub fn from_io(err: &io::Error) -> Self {
+        let mut user_err = Self::from_error(err).and_exit_code(ExitCode::from(err.kind()));
+        hints::apply(err, &mut user_err);
+        user_err
     }
 
     /// Create a new UserError.
     #[inline]
+    #[track_caller]
     pub fn from(message: &str) -> Self {
         Self::new(message.to_string())
     }
@@ -122,6 +891,57 @@ impl UserError {
         &self.message
     }
 
+    #[inline]
+    pub fn severity(&self) -> &Severity {
+        &self.details.severity
+    }
+
+    #[inline]
+    pub fn exit_code(&self) -> Option<ExitCode> {
+        self.details.exit_code
+    }
+
+    /// The stored [ExitCode], or [ExitCode::Failure] if there is none.
+    #[inline]
+    pub fn exit_code_or_default(&self) -> ExitCode {
+        self.details.exit_code.unwrap_or(ExitCode::Failure)
+    }
+
+    #[inline]
+    pub fn code(&self) -> Option<&str> {
+        self.details.code.as_deref()
+    }
+
+    #[inline]
+    pub fn snippet(&self) -> Option<&Snippet> {
+        self.details.snippet.as_ref()
+    }
+
+    /// The location in the source code at which this UserError was created, if known.
+    ///
+    /// It is rendered under the message when [Theme::show_location] is set.
+    /// # Examples
+    /// ```
+    /// use uerr::UserError;
+    ///
+    /// let err = UserError::from("could not open file");
+    ///
+    /// assert_eq!(err.location().unwrap().line(), line!() - 2);
+    /// ```
+    #[inline]
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        self.details.location
+    }
+
+    /// The [Backtrace] captured when this UserError was created, if any.
+    ///
+    /// It is rendered after the help by every renderer but [Display]. Frames of the standard
+    /// library and of this crate are hidden unless the backtrace style is `full`.
+    #[inline]
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.details.backtrace.as_ref()
+    }
+
     #[inline]
     pub const fn reasons(&self) -> &Vec<String> {
         &self.reasons
@@ -132,6 +952,16 @@ impl UserError {
         &mut self.reasons
     }
 
+    #[inline]
+    pub fn causes(&self) -> &Vec<UserError> {
+        &self.details.causes
+    }
+
+    #[inline]
+    pub fn causes_mut(&mut self) -> &mut Vec<UserError> {
+        &mut self.details.causes
+    }
+
     #[inline]
     pub const fn help(&self) -> &Vec<String> {
         &self.help
@@ -143,17 +973,80 @@ impl UserError {
     }
 }
 
+impl Debug for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UserError")
+            .field("message", &self.message)
+            .field("reasons", &self.reasons)
+            .field("help", &self.help)
+            .field("severity", &self.details.severity)
+            .field("exit_code", &self.details.exit_code)
+            .field("code", &self.details.code)
+            .field("snippet", &self.details.snippet)
+            .field("source", &self.details.source)
+            .field("backtrace", &self.details.backtrace)
+            .field("location", &self.details.location)
+            .field("causes", &self.details.causes)
+            .finish()
+    }
+}
+
+impl Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The backtrace is left out, as the Display text may become the message of another error.
+        let mut s = String::new();
+        self.render(&mut s, &"", &theme::theme(), false, None, false)?;
+        f.write_str(s.trim_end_matches('\n'))
+    }
+}
+
+impl Error for UserError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.details
+            .source
+            .as_deref()
+            .map(|err| err as &(dyn Error + 'static))
+    }
+}
+
+impl From<io::Error> for UserError {
+    /// See [UserError::from_io]. The [io::Error] is kept as the [Error::source].
+    #[inline]
+    #[track_caller]
+    fn from(err: io::Error) -> Self {
+        Self::from_io(&err).and_source(err)
+    }
+}
+
+impl From<Box<dyn Error>> for UserError {
+    #[inline]
+    #[track_caller]
+    fn from(err: Box<dyn Error>) -> Self {
+        Self::from_error(&*err)
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for UserError {
+    #[inline]
+    #[track_caller]
+    fn from(err: Box<dyn Error + Send + Sync>) -> Self {
+        Self::from_error(&*err)
+    }
+}
+
 /// A trait marking a type as able to be converted into an [UserError].
 /// # Examples
-/// ```
+/// ```no_run
 /// use std::fs;
 /// use uerr::IntoUserError;
 ///
 /// let contents = fs::read_to_string("names.txt")
 ///     .map_err(|err| {
+///         let code = err.raw_os_error().unwrap_or(-1);
+///
 ///         err.into_user_err()
 ///            .print_all("myprogram.exe: ")
-///            .exit(err.raw_os_error().unwrap_or(-1))
+///            .exit(code)
 ///     });
 /// ```
 pub trait IntoUserError {
@@ -165,13 +1058,48 @@ impl<D> IntoUserError for D
 where
     D: Display,
 {
+    #[track_caller]
     fn into_user_err(self) -> UserError {
         UserError::new(self.to_string())
     }
 }
 
+/// Returns the given value if it is a [UserError], or gives it back otherwise.
+///
+/// The blanket implementation of [IntoUserError] also covers UserError, flattening it into its
+/// [Display] text, so generic code which should keep its structure checks for it first.
+pub(crate) fn downcast_user_err<E>(err: E) -> Result<UserError, E>
+where
+    E: 'static,
+{
+    let mut slot = Some(err);
+    let any: &mut dyn Any = &mut slot;
+
+    match any.downcast_mut::<Option<UserError>>() {
+        Some(user_err) => Ok(user_err.take().expect("the slot is filled")),
+        None => Err(slot.expect("the slot is filled")),
+    }
+}
+
+/// Convert the given value with [IntoUserError], keeping a [UserError] whole; see
+/// [downcast_user_err].
+#[track_caller]
+pub(crate) fn to_user_err<E>(err: E) -> UserError
+where
+    E: IntoUserError + 'static,
+{
+    match downcast_user_err(err) {
+        Ok(user_err) => user_err,
+        Err(err) => err.into_user_err(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::error::Error;
+
+    use crate::severity::Severity;
+    use crate::theme::Theme;
     use crate::UserError;
 
     #[test]
@@ -183,4 +1111,309 @@ mod tests {
             .and_help("Filler help.")
             .print_all("program.exe: ");
     }
+
+    #[test]
+    fn render_styled() {
+        let mut s = String::new();
+
+        UserError::from("bad")
+            .and_help("fix it")
+            .fmt_with(&mut s, "e: ", &Theme::ASCII)
+            .unwrap();
+
+        assert_eq!(
+            s,
+            "\x1b[1;31me: bad\x1b[0m\n\x1b[36m + help: \x1b[0mfix it\n"
+        );
+    }
+
+    #[test]
+    fn display_and_error() {
+        let err = UserError::from("could not load config")
+            .and_reason("missing field `name`")
+            .and_help("Add a name.")
+            .and_source(std::io::Error::other("disk on fire"));
+
+        assert_eq!(
+            err.to_string(),
+            "could not load config\n - caused by: missing field `name`\n + help: Add a name."
+        );
+        assert_eq!(err.source().unwrap().to_string(), "disk on fire");
+
+        let boxed: Box<dyn Error> = Box::new(err);
+        assert!(boxed.source().is_some());
+    }
+
+    #[test]
+    fn source_chain() {
+        #[derive(Debug)]
+        struct Wrapper(&'static str, Option<Box<Wrapper>>);
+
+        impl std::fmt::Display for Wrapper {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.0)
+            }
+        }
+
+        impl Error for Wrapper {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                self.1.as_deref().map(|err| err as _)
+            }
+        }
+
+        let err = Wrapper(
+            "could not parse config",
+            Some(Box::new(Wrapper(
+                "could not read file: not found",
+                Some(Box::new(Wrapper("not found", None))),
+            ))),
+        );
+
+        let boxed: Box<dyn Error> = Box::new(err);
+        let user_err: UserError = boxed.into();
+
+        assert_eq!(user_err.message(), "could not parse config");
+        assert_eq!(user_err.reasons(), &["could not read file: not found"]);
+    }
+
+    #[test]
+    fn severity() {
+        let warning = UserError::warning("unused key");
+        let mut s = String::new();
+
+        warning
+            .fmt_with(&mut s, "warning: ", &Theme::ASCII)
+            .unwrap();
+
+        assert_eq!(s, "\x1b[1;33mwarning: unused key\x1b[0m\n");
+        assert!(!warning.severity().is_fatal());
+        assert!(UserError::from("fail").severity().is_fatal());
+        assert_eq!(
+            UserError::note("n")
+                .and_severity(Severity::Custom("bug".into()))
+                .severity()
+                .label(),
+            "bug"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "may not exit the process")]
+    fn non_fatal_exit() {
+        crate::testing::expect_exit(|| UserError::warning("unused key").exit(3));
+    }
+
+    #[test]
+    fn error_code() {
+        let err = UserError::from("could not find config").and_code("E0042");
+        let render = |prefix| {
+            let mut s = String::new();
+            err.fmt_to(&mut s, prefix).unwrap();
+            s
+        };
+
+        assert_eq!(render("error: "), "error[E0042]: could not find config\n");
+        assert_eq!(render("prog "), "prog [E0042] could not find config\n");
+        assert_eq!(err.to_string(), "[E0042] could not find config");
+    }
+
+    #[test]
+    fn wrapping() {
+        let err = UserError::from("could not read the configuration file")
+            .and_reason("The system cannot find the file specified.")
+            .and_help("Does this file exist?");
+
+        let mut s = String::new();
+        let theme = Theme::ASCII.without_styles().with_max_width(30);
+
+        err.fmt_with(&mut s, "error: ", &theme).unwrap();
+        assert_eq!(
+            s,
+            "error: could not read the
+       configuration file
+ - caused by: The system
+     |        cannot find the
+     |        file specified.
+ + help: Does this file exist?
+"
+        );
+    }
+
+    #[test]
+    fn cause_tree() {
+        let host = |name: &str| {
+            UserError::from(format!("{name}: connection refused").as_str())
+                .and_cause(UserError::from("port 22 is closed"))
+        };
+
+        let err = UserError::from("deployment failed")
+            .and_cause(host("web-1"))
+            .and_cause(host("web-2"))
+            .and_cause(host("web-3"))
+            .and_help("Retry with `--hosts`.");
+
+        let render = |theme: Theme| {
+            let mut s = String::new();
+            err.fmt_with(&mut s, "error: ", &theme.without_styles())
+                .unwrap();
+            s
+        };
+
+        let theme = Theme {
+            max_causes: Some(2),
+            ..Theme::ASCII
+        };
+
+        assert_eq!(
+            render(theme),
+            "error: deployment failed
+ - caused by: |- web-1: connection refused
+     |        |   - caused by: `- port 22 is closed
+     |        |- web-2: connection refused
+     |        |   - caused by: `- port 22 is closed
+     |        `- ... and 1 more
+ + help: Retry with `--hosts`.
+"
+        );
+
+        let theme = Theme {
+            max_cause_depth: Some(1),
+            ..Theme::ASCII
+        };
+
+        assert_eq!(
+            render(theme).lines().nth(2),
+            Some("     |        |   - caused by: `- ... and 1 more")
+        );
+    }
+
+    #[test]
+    fn multi_line() {
+        use crate::theme::BlankLines;
+
+        let err = UserError::from("invalid configuration\nin a.toml")
+            .and_reason("expected a table  \n\n\n\nfound a string")
+            .and_help("\nSee the manual.\n");
+
+        let render = |theme: Theme| {
+            let mut s = String::new();
+            err.fmt_with(&mut s, "error: ", &theme.without_styles())
+                .unwrap();
+            s
+        };
+
+        assert_eq!(
+            render(Theme::ASCII),
+            "error: invalid configuration
+       in a.toml
+ - caused by: expected a table
+     |
+     |        found a string
+ + help: See the manual.
+"
+        );
+
+        let theme = Theme {
+            blank_lines: BlankLines::Remove,
+            trim_trailing_whitespace: false,
+            ..Theme::MINIMAL
+        };
+
+        assert_eq!(
+            render(theme),
+            "error: invalid configuration
+       in a.toml
+  caused by: expected a table  
+             found a string
+  help: See the manual.
+"
+        );
+    }
+
+    #[test]
+    fn caller_location() {
+        use crate::{IntoUserError, ResultExt};
+
+        let line = line!();
+        let errs = [
+            UserError::from("a"),
+            "b".into_user_err(),
+            std::io::Error::other("c").into(),
+            "x".parse::<u8>().user_err("d").unwrap_err(),
+        ];
+
+        for (i, err) in errs.iter().enumerate() {
+            let location = err.location().unwrap();
+
+            assert_eq!(location.file(), file!());
+            assert_eq!(location.line(), line + 2 + i as u32);
+        }
+
+        let mut s = String::new();
+        let theme = Theme::ASCII.without_styles().with_location(true);
+
+        errs[0].fmt_with(&mut s, "error: ", &theme).unwrap();
+        assert_eq!(s, format!("error: a\n at src/lib.rs:{}:13\n", line + 2));
+    }
+
+    #[test]
+    fn io_hints() {
+        use crate::hints::{self, Hint};
+        use std::io::ErrorKind;
+
+        let user_err: UserError = std::io::Error::from(ErrorKind::WriteZero).into();
+        assert!(user_err.help().is_empty());
+
+        hints::extend_hint(ErrorKind::WriteZero, Hint::new().and_help("extended"));
+        hints::set_hint(ErrorKind::WriteZero, Hint::new().and_reason("stale"));
+        hints::set_hint(ErrorKind::WriteZero, Hint::new().and_reason("replaced"));
+
+        let user_err: UserError = std::io::Error::from(ErrorKind::WriteZero).into();
+
+        // The table is global, so it is restored before any assertion can fail.
+        hints::reset_hints();
+
+        assert_eq!(user_err.reasons(), &["replaced"]);
+        assert_eq!(user_err.help(), &["extended"]);
+        assert!(user_err.source().is_some());
+    }
+
+    #[test]
+    fn backtrace_in_sinks() {
+        let err = UserError::from("x").and_backtrace(std::backtrace::Backtrace::force_capture());
+
+        let mut s = String::new();
+        err.fmt_to(&mut s, "error: ").unwrap();
+        assert!(s.starts_with("error: x\n backtrace:\n"), "{s}");
+
+        let mut buf = Vec::new();
+        err.write_to(&mut buf, "error: ").unwrap();
+        assert_eq!(buf, s.as_bytes());
+
+        let exit = crate::testing::expect_exit(|| err.print_all("error: ").exit(1));
+        assert_eq!(exit.output(), s);
+
+        assert_eq!(err.to_string(), "x");
+    }
+
+    #[test]
+    fn render_to_sinks() {
+        let err = UserError::from("could not open file")
+            .and_reason("The system cannot find the file specified.")
+            .and_reason("Filler reason.")
+            .and_help("Does this file exist?");
+
+        let expected = "program.exe: could not open file\n \
+                        - caused by: The system cannot find the file specified.\n     \
+                        |        Filler reason.\n \
+                        + help: Does this file exist?\n";
+
+        let mut s = String::new();
+        err.fmt_to(&mut s, "program.exe: ").unwrap();
+        assert_eq!(s, expected);
+
+        let mut buf = Vec::new();
+        err.write_to(&mut buf, "program.exe: ").unwrap();
+        assert_eq!(buf, expected.as_bytes());
+    }
 }
@@ -0,0 +1,211 @@
+//! Macros for constructing and returning [UserError](crate::UserError)s.
+
+/// Construct a [UserError](crate::UserError) from formatted text.
+///
+/// The message may be followed by a `;` and a comma-separated list of `reason = ...`,
+/// `help = ...`, `code = ...` and `exit = ...` attributes, each of which may be repeated. A single
+/// expression which is not a string literal is converted with
+/// [IntoUserError](crate::IntoUserError), unless it is already a UserError, which is kept whole
+/// rather than flattened into its message.
+/// # Examples
+/// ```
+/// use uerr::uerr;
+///
+/// let path = "config.toml";
+/// let err = uerr!("failed to read {path}"; reason = "the file is empty", help = "Run `init`.");
+///
+/// assert_eq!(err.message(), "failed to read config.toml");
+/// assert_eq!(err.reasons(), &["the file is empty"]);
+/// assert_eq!(err.help(), &["Run `init`."]);
+///
+/// let err = uerr!("x".parse::<u8>().unwrap_err(); code = "E0001", exit = 65);
+/// assert_eq!(err.code(), Some("E0001"));
+///
+/// let err = uerr!(err; help = "Pass a number.");
+/// assert_eq!(err.message(), "invalid digit found in string");
+/// assert_eq!(err.code(), Some("E0001"));
+/// assert_eq!(err.help(), &["Pass a number."]);
+/// ```
+#[macro_export]
+macro_rules! uerr {
+    ($fmt:literal $(, $arg:expr)* $(,)? $(; $($key:ident = $value:expr),* $(,)?)?) => {{
+        let err = $crate::UserError::new(::std::format!($fmt $(, $arg)*));
+        $($(let err = $crate::__uerr_attr!(err, $key, $value);)*)?
+        err
+    }};
+    ($err:expr $(; $($key:ident = $value:expr),* $(,)?)?) => {{
+        let err = match $err {
+            err => {
+                #[allow(unused_imports)]
+                use $crate::__private::{IntoTag as _, UserErrorTag as _};
+                (&err).uerr_kind().convert(err)
+            }
+        };
+        $($(let err = $crate::__uerr_attr!(err, $key, $value);)*)?
+        err
+    }};
+}
+
+/// Items used by the expansions of [uerr!] and of the `UserError` derive.
+///
+/// [uerr!] keeps a [UserError](crate::UserError) whole where
+/// [IntoUserError::into_user_err](crate::IntoUserError::into_user_err) would flatten it: method
+/// resolution on `(&err).uerr_kind()` finds [__private::UserErrorTag] without autoref for a
+/// UserError, and falls back to [__private::IntoTag] with autoref for any other type.
+#[doc(hidden)]
+pub mod __private {
+    use std::error::Error;
+
+    use crate::{IntoUserError, UserError};
+
+    /// Views a `#[uerr(source)]` field as a `dyn Error`.
+    ///
+    /// Method resolution dereferences a field such as `Box<dyn Error + Send + Sync>`, which is
+    /// not an Error itself, down to the trait object.
+    pub trait AsDynError<'a> {
+        fn as_dyn_error(&self) -> &(dyn Error + 'a);
+    }
+
+    impl<'a, T> AsDynError<'a> for T
+    where
+        T: Error + 'a,
+    {
+        #[inline]
+        fn as_dyn_error(&self) -> &(dyn Error + 'a) {
+            self
+        }
+    }
+
+    impl<'a> AsDynError<'a> for dyn Error + 'a {
+        #[inline]
+        fn as_dyn_error(&self) -> &(dyn Error + 'a) {
+            self
+        }
+    }
+
+    impl<'a> AsDynError<'a> for dyn Error + Send + 'a {
+        #[inline]
+        fn as_dyn_error(&self) -> &(dyn Error + 'a) {
+            self
+        }
+    }
+
+    impl<'a> AsDynError<'a> for dyn Error + Send + Sync + 'a {
+        #[inline]
+        fn as_dyn_error(&self) -> &(dyn Error + 'a) {
+            self
+        }
+    }
+
+    pub struct UserErrorKind;
+
+    pub struct IntoKind;
+
+    pub trait UserErrorTag {
+        #[inline]
+        fn uerr_kind(&self) -> UserErrorKind {
+            UserErrorKind
+        }
+    }
+
+    impl UserErrorTag for UserError {}
+
+    pub trait IntoTag {
+        #[inline]
+        fn uerr_kind(&self) -> IntoKind {
+            IntoKind
+        }
+    }
+
+    impl<T> IntoTag for &T where T: IntoUserError {}
+
+    impl UserErrorKind {
+        #[inline]
+        pub fn convert(self, err: UserError) -> UserError {
+            err
+        }
+    }
+
+    impl IntoKind {
+        #[inline]
+        #[track_caller]
+        pub fn convert<T>(self, err: T) -> UserError
+        where
+            T: IntoUserError,
+        {
+            err.into_user_err()
+        }
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __uerr_attr {
+    ($err:ident, reason, $value:expr) => {
+        $err.and_reason($value)
+    };
+    ($err:ident, help, $value:expr) => {
+        $err.and_help($value)
+    };
+    ($err:ident, code, $value:expr) => {
+        $err.and_code($value)
+    };
+    ($err:ident, exit, $value:expr) => {
+        $err.and_exit_code($value)
+    };
+}
+
+/// Return early with a [UserError](crate::UserError) constructed by [uerr!].
+///
+/// The error is converted with [Into], so the function may return any error type which a
+/// UserError converts into, such as `Box<dyn Error>`.
+/// # Examples
+/// ```
+/// use uerr::{bail, UserError};
+///
+/// fn parse_port(s: &str) -> Result<u16, UserError> {
+///     match s.parse() {
+///         Ok(0) => bail!("port 0 is reserved"; help = "Choose a port above 1023."),
+///         Ok(port) => Ok(port),
+///         Err(err) => bail!("invalid port `{s}`"; reason = err.to_string()),
+///     }
+/// }
+///
+/// assert_eq!(parse_port("0").unwrap_err().message(), "port 0 is reserved");
+/// ```
+#[macro_export]
+macro_rules! bail {
+    ($($tt:tt)+) => {
+        return ::std::result::Result::Err(::std::convert::Into::into($crate::uerr!($($tt)+)))
+    };
+}
+
+/// Return early with a [UserError](crate::UserError) if the given condition is false.
+///
+/// Without a message, the message names the failed condition.
+/// # Examples
+/// ```
+/// use uerr::{ensure, UserError};
+///
+/// fn check(threads: usize) -> Result<(), UserError> {
+///     ensure!(threads > 0, "at least one thread is required"; help = "Pass `--threads 1`.");
+///     ensure!(threads <= 64);
+///     Ok(())
+/// }
+///
+/// assert_eq!(check(0).unwrap_err().help(), &["Pass `--threads 1`."]);
+/// assert_eq!(check(65).unwrap_err().message(), "condition failed: `threads <= 64`");
+/// ```
+#[macro_export]
+macro_rules! ensure {
+    ($cond:expr $(,)?) => {
+        if !$cond {
+            $crate::bail!(::std::concat!("condition failed: `", ::std::stringify!($cond), "`"));
+        }
+    };
+    ($cond:expr, $($tt:tt)+) => {
+        if !$cond {
+            $crate::bail!($($tt)+);
+        }
+    };
+}
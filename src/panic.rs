@@ -0,0 +1,171 @@
+//! A panic hook which renders panics as [UserError] reports.
+use std::any::Any;
+use std::borrow::Cow;
+use std::panic::{self, Location, PanicHookInfo};
+use std::thread;
+
+use crate::severity::Severity;
+use crate::UserError;
+
+/// Configures the panic hook installed by [PanicHook::install].
+/// # Examples
+/// ```no_run
+/// uerr::panic::PanicHook::new()
+///     .bug_url("https://github.com/ImajinDevon/uerr/issues/new")
+///     .install();
+///
+/// panic!("unreachable state");
+/// ```
+/// ```text
+/// panic: unreachable state
+///  - caused by: thread `main` panicked at src/main.rs:6:1
+///  + help: This is a bug. Please report it at https://github.com/ImajinDevon/uerr/issues/new
+///      |   note: run with `RUST_BACKTRACE=1` to display a backtrace
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PanicHook {
+    bug_url: Option<Cow<'static, str>>,
+    hide_backtrace: bool,
+}
+
+impl PanicHook {
+    /// Create a new PanicHook, which captures backtraces according to `RUST_BACKTRACE`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            bug_url: None,
+            hide_backtrace: false,
+        }
+    }
+
+    /// Set the URL at which users should report bugs.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn bug_url(mut self, url: impl Into<Cow<'static, str>>) -> Self {
+        self.bug_url = Some(url.into());
+        self
+    }
+
+    /// Set whether backtraces are displayed. Defaults to true.
+    ///
+    /// Even when enabled, a backtrace is only captured if `RUST_LIB_BACKTRACE` or
+    /// `RUST_BACKTRACE` asks for one, as with [UserError::new]; otherwise the help explains how
+    /// to enable it.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub const fn backtrace(mut self, enabled: bool) -> Self {
+        self.hide_backtrace = !enabled;
+        self
+    }
+
+    /// Convert the given panic into a [UserError].
+    ///
+    /// The message is taken from the payload, the location becomes a reason, and the help asks
+    /// users to report the bug. No backtrace is captured if disabled with [PanicHook::backtrace].
+    pub fn report(&self, info: &PanicHookInfo<'_>) -> UserError {
+        self.report_payload(info.payload(), info.location())
+    }
+
+    fn report_payload(
+        &self,
+        payload: &(dyn Any + Send),
+        location: Option<&Location<'_>>,
+    ) -> UserError {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("Box<dyn Any>");
+
+        let err = if self.hide_backtrace {
+            UserError::without_backtrace(message.to_string())
+        } else {
+            UserError::from(message)
+        };
+        let mut err = err.and_severity(Severity::Custom(Cow::Borrowed("panic")));
+
+        let thread = thread::current();
+        let thread = thread.name().unwrap_or("<unnamed>");
+
+        match location {
+            Some(location) => err.add_reason(format!("thread `{thread}` panicked at {location}")),
+            None => err.add_reason(format!("thread `{thread}` panicked")),
+        }
+
+        match &self.bug_url {
+            Some(url) => err.add_help(format!("This is a bug. Please report it at {url}")),
+            None => err.add_help("This is a bug. Please report it to the developers."),
+        }
+        err
+    }
+
+    /// Install this PanicHook, replacing the current panic hook.
+    ///
+    /// The backtrace captured by [UserError::new] is rendered after the help.
+    pub fn install(self) {
+        panic::set_hook(Box::new(move |info| {
+            let mut err = self.report(info);
+
+            if !self.hide_backtrace && err.backtrace().is_none() {
+                err.add_help("note: run with `RUST_BACKTRACE=1` to display a backtrace");
+            }
+            err.print();
+        }));
+    }
+}
+
+/// Install a [PanicHook] with the default configuration.
+#[inline]
+pub fn install_panic_hook() {
+    PanicHook::new().install();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::Location;
+    use std::thread;
+
+    use super::PanicHook;
+
+    #[test]
+    fn report() {
+        let hook = PanicHook::new().bug_url("https://example.com/issues");
+        let location = Location::caller();
+        let err = hook.report_payload(&"boom", Some(location));
+
+        let thread = thread::current();
+        let thread = thread.name().unwrap_or("<unnamed>");
+
+        assert_eq!(err.message(), "boom");
+        assert_eq!(err.severity().to_string(), "panic");
+        assert_eq!(
+            err.reasons(),
+            &[format!("thread `{thread}` panicked at {location}")]
+        );
+        assert_eq!(
+            err.help(),
+            &["This is a bug. Please report it at https://example.com/issues"]
+        );
+    }
+
+    #[test]
+    fn report_payloads() {
+        let hook = PanicHook::new();
+
+        let err = hook.report_payload(&String::from("formatted 1"), None);
+        assert_eq!(err.message(), "formatted 1");
+        assert!(err.reasons()[0].ends_with(" panicked"));
+        assert_eq!(
+            err.help(),
+            &["This is a bug. Please report it to the developers."]
+        );
+
+        let err = hook.report_payload(&1_u8, None);
+        assert_eq!(err.message(), "Box<dyn Any>");
+
+        let err = hook.backtrace(false).report_payload(&"boom", None);
+        assert!(err.backtrace().is_none());
+    }
+}
@@ -0,0 +1,127 @@
+//! A [Termination] wrapper for returning a [UserError] from `main`.
+use std::process::{self, Termination};
+
+use crate::exit::ExitCode;
+use crate::UserError;
+
+/// The result of `main`, rendered with [UserError::print] on failure instead of Rust's default
+/// `Error: {:?}` output.
+///
+/// On failure, the process exits with the [ExitCode](crate::exit::ExitCode) stored on the
+/// [UserError], or [ExitCode::Failure] if there is none or it lies outside `1..=255`, so that a
+/// failure is never reported as a success.
+/// # Examples
+/// ```no_run
+/// use uerr::exit::ExitCode;
+/// use uerr::{Report, UserError};
+///
+/// fn run() -> Result<(), UserError> {
+///     Err(UserError::from("missing configuration").and_exit_code(ExitCode::Config))
+/// }
+///
+/// fn main() -> Report {
+///     run().into()
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Report(Result<(), UserError>);
+
+impl Report {
+    /// Run the given function, capturing its result.
+    #[inline]
+    pub fn run<F, E>(f: F) -> Self
+    where
+        F: FnOnce() -> Result<(), E>,
+        E: Into<UserError>,
+    {
+        f().into()
+    }
+
+    /// Returns the contained result.
+    #[inline]
+    pub fn into_result(self) -> Result<(), UserError> {
+        self.0
+    }
+}
+
+impl<E> From<Result<(), E>> for Report
+where
+    E: Into<UserError>,
+{
+    #[inline]
+    fn from(res: Result<(), E>) -> Self {
+        Self(res.map_err(Into::into))
+    }
+}
+
+impl Termination for Report {
+    fn report(self) -> process::ExitCode {
+        match self.0 {
+            Ok(()) => process::ExitCode::SUCCESS,
+            Err(err) => {
+                err.print();
+                status(err.exit_code_or_default())
+            }
+        }
+    }
+}
+
+/// The status of a failed process exiting with the given code, which is [ExitCode::Failure] if
+/// the code lies outside `1..=255`.
+fn status(code: ExitCode) -> process::ExitCode {
+    let code = u8::try_from(code.code())
+        .ok()
+        .filter(|code| *code != 0)
+        .unwrap_or(ExitCode::Failure.code() as u8);
+
+    process::ExitCode::from(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::{self, Termination};
+
+    use super::{status, Report};
+    use crate::exit::{self, ExitCode};
+    use crate::testing::expect_exit;
+    use crate::UserError;
+
+    #[test]
+    fn stored_exit_code() {
+        let report =
+            Report::run(|| Err(UserError::from("bad config").and_exit_code(ExitCode::Config)));
+        let err = report.into_result().unwrap_err();
+
+        assert_eq!(err.exit_code_or_default(), ExitCode::Config);
+        assert_eq!(
+            UserError::from("x").exit_code_or_default(),
+            ExitCode::Failure
+        );
+    }
+
+    #[test]
+    fn exit_status() {
+        assert_eq!(status(ExitCode::Config), process::ExitCode::from(78));
+        assert_eq!(status(ExitCode::Other(256)), process::ExitCode::FAILURE);
+        assert_eq!(status(ExitCode::Other(-1)), process::ExitCode::FAILURE);
+        assert_eq!(status(ExitCode::Ok), process::ExitCode::FAILURE);
+    }
+
+    #[test]
+    fn reported_status() {
+        assert_eq!(
+            Report::from(Ok::<(), UserError>(())).report(),
+            process::ExitCode::SUCCESS
+        );
+
+        // The report is captured by exiting afterwards, keeping it off the stderr of the tests.
+        let exit = expect_exit(|| {
+            let err = UserError::from("bad config").and_exit_code(ExitCode::Config);
+
+            assert_eq!(Report::from(Err(err)).report(), process::ExitCode::from(78));
+            exit::exit(0)
+        });
+
+        assert!(exit.output().starts_with("error: bad config\n"));
+    }
+}
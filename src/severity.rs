@@ -0,0 +1,63 @@
+//! The severity of a [UserError](crate::UserError).
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+
+use crate::style::{Style, Styles};
+
+/// How severe a [UserError](crate::UserError) is.
+///
+/// The severity determines the default prefix used by [UserError::print](crate::UserError::print),
+/// the style of the message, and whether
+/// [UserError::exit_if_fatal](crate::UserError::exit_if_fatal) terminates the process.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// A failure. This is the default.
+    #[default]
+    Error,
+    /// A potential problem which does not prevent the program from continuing.
+    Warning,
+    /// General information.
+    Info,
+    /// Additional context, usually following another diagnostic.
+    Note,
+    /// A fatal severity with a custom label.
+    Custom(Cow<'static, str>),
+}
+
+impl Severity {
+    /// The label of this Severity, such as `error` or `warning`.
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Info => "info",
+            Self::Note => "note",
+            Self::Custom(label) => label,
+        }
+    }
+
+    /// Returns true if a diagnostic of this Severity should terminate the process.
+    ///
+    /// Only [Severity::Error] and [Severity::Custom] are fatal.
+    #[inline]
+    pub const fn is_fatal(&self) -> bool {
+        matches!(self, Self::Error | Self::Custom(_))
+    }
+
+    /// The style of this Severity within the given [Styles].
+    pub const fn style(&self, styles: &Styles) -> Style {
+        match self {
+            Self::Error | Self::Custom(_) => styles.message,
+            Self::Warning => styles.warning,
+            Self::Info => styles.info,
+            Self::Note => styles.note,
+        }
+    }
+}
+
+impl Display for Severity {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
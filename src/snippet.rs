@@ -0,0 +1,687 @@
+//! Source code excerpts with labeled spans, rendered beneath the message of a
+//! [UserError](crate::UserError).
+use std::collections::BTreeSet;
+use std::fmt;
+use std::ops::Range;
+
+use crate::style::{Style, Styles};
+use crate::width::{str_width, TAB_WIDTH};
+
+/// A labeled byte range within a [Snippet].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Label {
+    range: Range<usize>,
+    message: String,
+    primary: bool,
+}
+
+impl Label {
+    /// Create a new primary Label, underlined with `^`.
+    ///
+    /// The message may be empty.
+    #[inline]
+    pub fn primary(range: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            range,
+            message: message.into(),
+            primary: true,
+        }
+    }
+
+    /// Create a new secondary Label, underlined with `-`.
+    ///
+    /// The message may be empty.
+    #[inline]
+    pub fn secondary(range: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            range,
+            message: message.into(),
+            primary: false,
+        }
+    }
+
+    #[inline]
+    pub fn range(&self) -> &Range<usize> {
+        &self.range
+    }
+
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    #[inline]
+    pub const fn is_primary(&self) -> bool {
+        self.primary
+    }
+}
+
+/// A named source text with [Label]s pointing into it.
+/// # Examples
+/// ```
+/// use uerr::snippet::{Label, Snippet};
+/// use uerr::UserError;
+///
+/// let source = "name = \"uerr\"\nversion = 1\n";
+///
+/// let err = UserError::from("invalid type: integer `1`, expected a string")
+///     .and_snippet(
+///         Snippet::new("Cargo.toml", source)
+///             .and_label(Label::primary(24..25, "expected a string")),
+///     );
+///
+/// assert_eq!(
+///     err.to_string(),
+///     "invalid type: integer `1`, expected a string
+///  --> Cargo.toml:2:11
+///   |
+/// 2 | version = 1
+///   |           ^ expected a string"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Snippet {
+    name: String,
+    source: String,
+    labels: Vec<Label>,
+}
+
+impl Snippet {
+    /// Create a new Snippet with the given name, usually a file path, and source text.
+    #[inline]
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            source: source.into(),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Add a [Label] to this Snippet.
+    #[inline]
+    pub fn add_label(&mut self, label: Label) {
+        self.labels.push(label);
+    }
+
+    /// Add a [Label] to this Snippet.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub fn and_label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    #[inline]
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    fn lines(&self) -> Vec<Line<'_>> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+
+        for text in self.source.split_inclusive('\n') {
+            lines.push(Line {
+                start,
+                text: text.trim_end_matches('\n').trim_end_matches('\r'),
+            });
+            start += text.len();
+        }
+
+        if lines.is_empty() {
+            lines.push(Line { start: 0, text: "" });
+        }
+        lines
+    }
+
+    /// Clamp the given offset into the source, rounding down to a character boundary.
+    fn clamp(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.source.len());
+
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+
+    /// The 1-based line and character column of the given byte offset.
+    ///
+    /// Offsets beyond the end of the source are clamped.
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let lines = self.lines();
+        let (line, byte) = locate(&lines, self.clamp(offset));
+        (line + 1, lines[line].text[..byte].chars().count() + 1)
+    }
+
+    fn resolve<'a>(&self, lines: &[Line<'_>], label: &'a Label) -> Mark<'a> {
+        let start = self.clamp(label.range.start);
+        let mut end = self.clamp(label.range.end.max(start));
+
+        if end > start && self.source[..end].ends_with('\n') {
+            end -= 1;
+
+            if end > start && self.source[..end].ends_with('\r') {
+                end -= 1;
+            }
+        }
+
+        let (start_line, start_byte) = locate(lines, start);
+        let (end_line, end_byte) = locate(lines, end);
+        let start_col = str_width(&lines[start_line].text[..start_byte]);
+        let mut end_col = str_width(&lines[end_line].text[..end_byte]);
+
+        if end_line == start_line && end_col <= start_col {
+            end_col = start_col + 1;
+        }
+
+        let indent = lines[start_line].text.len() - lines[start_line].text.trim_start().len();
+
+        Mark {
+            label,
+            start_line,
+            start_col,
+            start_char: lines[start_line].text[..start_byte].chars().count(),
+            end_line,
+            end_col,
+            slash: start_byte <= indent,
+        }
+    }
+
+    pub(crate) fn render<W>(
+        &self,
+        w: &mut W,
+        gutter: &str,
+        styles: &Styles,
+        primary: Style,
+    ) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        let lines = self.lines();
+        let marks: Vec<_> = self
+            .labels
+            .iter()
+            .map(|label| self.resolve(&lines, label))
+            .collect();
+
+        let (single, multi): (Vec<_>, Vec<_>) = marks
+            .iter()
+            .partition(|mark| mark.start_line == mark.end_line);
+
+        let mut shown = BTreeSet::new();
+
+        for mark in &marks {
+            if mark.end_line - mark.start_line <= 3 {
+                shown.extend(mark.start_line..=mark.end_line);
+            } else {
+                shown.extend([
+                    mark.start_line,
+                    mark.start_line + 1,
+                    mark.end_line - 1,
+                    mark.end_line,
+                ]);
+            }
+        }
+
+        let width = shown.last().map_or(1, |&last| (last + 1).to_string().len());
+
+        let painter = Painter {
+            styles,
+            primary,
+            gutter,
+            width,
+            margin: multi.len() * 2,
+        };
+
+        let head = marks
+            .iter()
+            .find(|mark| mark.label.primary)
+            .or(marks.first());
+
+        match head {
+            Some(mark) => writeln!(
+                w,
+                "{:width$}{} {}:{}:{}",
+                "",
+                styles.gutter.paint("-->"),
+                self.name,
+                mark.start_line + 1,
+                mark.start_char + 1
+            )?,
+            None => writeln!(
+                w,
+                "{:width$}{} {}",
+                "",
+                styles.gutter.paint("-->"),
+                self.name
+            )?,
+        }
+        painter.blank(w)?;
+
+        let mut open = vec![false; multi.len()];
+        let mut previous = None;
+
+        for &line in &shown {
+            if previous.is_some_and(|previous| previous + 1 != line) {
+                painter.elision(w, painter.margin_row(&multi, &open))?;
+            }
+            previous = Some(line);
+
+            let mut row = Row::default();
+
+            for (k, mark) in multi.iter().enumerate() {
+                if mark.start_line == line && mark.slash {
+                    open[k] = true;
+                    row.put(k * 2, '/', mark.style(&painter));
+                } else if open[k] {
+                    row.put(k * 2, '|', mark.style(&painter));
+                }
+            }
+
+            write!(
+                w,
+                "{} ",
+                styles
+                    .gutter
+                    .paint(format_args!("{:>width$} {gutter}", line + 1))
+            )?;
+            row.pad(painter.margin);
+            row.write(w)?;
+            writeln!(w, "{}", expand(lines[line].text).trim_end())?;
+
+            for (k, mark) in multi.iter().enumerate() {
+                if mark.start_line != line || mark.slash {
+                    continue;
+                }
+
+                let mut row = painter.margin_row(&multi, &open);
+
+                for col in k * 2 + 1..painter.margin + mark.start_col {
+                    row.put(col, '_', mark.style(&painter));
+                }
+                row.put(
+                    painter.margin + mark.start_col,
+                    mark.marker(),
+                    mark.style(&painter),
+                );
+                painter.row(w, row)?;
+                open[k] = true;
+            }
+
+            let on_line: Vec<_> = single
+                .iter()
+                .filter(|mark| mark.start_line == line)
+                .copied()
+                .collect();
+
+            if !on_line.is_empty() {
+                painter.single(w, &on_line, &multi, &open)?;
+            }
+
+            for (k, mark) in multi.iter().enumerate() {
+                if mark.end_line != line {
+                    continue;
+                }
+
+                let mut row = painter.margin_row(&multi, &open);
+
+                for col in k * 2 + 1..painter.margin + mark.end_col - 1 {
+                    row.put(col, '_', mark.style(&painter));
+                }
+                row.put(
+                    painter.margin + mark.end_col - 1,
+                    mark.marker(),
+                    mark.style(&painter),
+                );
+                row.message(&mark.label.message, mark.style(&painter));
+                painter.row(w, row)?;
+                open[k] = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A line of source text, excluding its line terminator.
+struct Line<'a> {
+    start: usize,
+    text: &'a str,
+}
+
+/// Find the line containing the given offset, and the byte offset within that line.
+fn locate(lines: &[Line<'_>], offset: usize) -> (usize, usize) {
+    let index = lines.partition_point(|line| line.start <= offset).max(1) - 1;
+    let line = &lines[index];
+    (index, (offset - line.start).min(line.text.len()))
+}
+
+fn expand(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+/// A [Label] resolved to display columns.
+struct Mark<'a> {
+    label: &'a Label,
+    start_line: usize,
+    start_col: usize,
+    start_char: usize,
+    end_line: usize,
+    end_col: usize,
+    /// Whether a multi-line span starts at the indentation of its first line.
+    slash: bool,
+}
+
+impl Mark<'_> {
+    const fn marker(&self) -> char {
+        if self.label.primary {
+            '^'
+        } else {
+            '-'
+        }
+    }
+
+    const fn style(&self, painter: &Painter<'_>) -> Style {
+        if self.label.primary {
+            painter.primary
+        } else {
+            painter.styles.gutter
+        }
+    }
+}
+
+/// An annotation row, made of styled cells followed by an optional message.
+#[derive(Default)]
+struct Row {
+    cells: Vec<(char, Style)>,
+    message: Option<(String, Style)>,
+}
+
+impl Row {
+    fn pad(&mut self, len: usize) {
+        if self.cells.len() < len {
+            self.cells.resize(len, (' ', Style::new()));
+        }
+    }
+
+    fn put(&mut self, col: usize, ch: char, style: Style) {
+        self.pad(col + 1);
+        self.cells[col] = (ch, style);
+    }
+
+    fn message(&mut self, message: &str, style: Style) {
+        if !message.is_empty() {
+            self.message = Some((message.to_string(), style));
+        }
+    }
+
+    fn write<W>(&self, w: &mut W) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        let mut cells = self.cells.as_slice();
+
+        while let Some((&(_, style), _)) = cells.split_first() {
+            let len = cells
+                .iter()
+                .position(|&(_, s)| s != style)
+                .unwrap_or(cells.len());
+            let run: String = cells[..len].iter().map(|&(ch, _)| ch).collect();
+
+            write!(w, "{}", style.paint(run))?;
+            cells = &cells[len..];
+        }
+        Ok(())
+    }
+}
+
+struct Painter<'a> {
+    styles: &'a Styles,
+    primary: Style,
+    gutter: &'a str,
+    width: usize,
+    margin: usize,
+}
+
+impl Painter<'_> {
+    fn blank<W>(&self, w: &mut W) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        writeln!(
+            w,
+            "{}",
+            self.styles
+                .gutter
+                .paint(format_args!("{:w$} {}", "", self.gutter, w = self.width))
+        )
+    }
+
+    /// Render the `...` line standing in for elided source lines, continuing the given margin.
+    fn elision<W>(&self, w: &mut W, mut row: Row) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        while row.cells.last().is_some_and(|&(ch, _)| ch == ' ') {
+            row.cells.pop();
+        }
+
+        write!(w, "{}", self.styles.gutter.paint("..."))?;
+
+        if row.cells.is_empty() {
+            return writeln!(w);
+        }
+
+        // The margin starts at the same column as on source lines, after `{line} {gutter} `.
+        let column = self.width + str_width(self.gutter) + 2;
+        write!(w, "{:w$}", "", w = column.saturating_sub(3).max(1))?;
+        row.write(w)?;
+        writeln!(w)
+    }
+
+    fn margin_row(&self, multi: &[&Mark<'_>], open: &[bool]) -> Row {
+        let mut row = Row::default();
+
+        for (k, mark) in multi.iter().enumerate() {
+            if open[k] {
+                row.put(k * 2, '|', mark.style(self));
+            }
+        }
+        row
+    }
+
+    fn row<W>(&self, w: &mut W, mut row: Row) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        while row.cells.last().is_some_and(|&(ch, _)| ch == ' ') {
+            row.cells.pop();
+        }
+
+        write!(
+            w,
+            "{}",
+            self.styles
+                .gutter
+                .paint(format_args!("{:w$} {}", "", self.gutter, w = self.width))
+        )?;
+
+        if row.cells.is_empty() && row.message.is_none() {
+            return writeln!(w);
+        }
+
+        write!(w, " ")?;
+        row.write(w)?;
+
+        if let Some((message, style)) = &row.message {
+            write!(w, " {}", style.paint(message))?;
+        }
+        writeln!(w)
+    }
+
+    /// Render the underlines and messages of the single-line marks on a line.
+    fn single<W>(
+        &self,
+        w: &mut W,
+        marks: &[&Mark<'_>],
+        multi: &[&Mark<'_>],
+        open: &[bool],
+    ) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        let mut row = self.margin_row(multi, open);
+
+        for mark in marks.iter().filter(|m| !m.label.primary) {
+            for col in mark.start_col..mark.end_col {
+                row.put(self.margin + col, mark.marker(), mark.style(self));
+            }
+        }
+
+        for mark in marks.iter().filter(|m| m.label.primary) {
+            for col in mark.start_col..mark.end_col {
+                row.put(self.margin + col, mark.marker(), mark.style(self));
+            }
+        }
+
+        let mut pending: Vec<_> = marks
+            .iter()
+            .filter(|mark| !mark.label.message.is_empty())
+            .copied()
+            .collect();
+        pending.sort_by_key(|mark| mark.start_col);
+
+        let max_end = marks.iter().map(|mark| mark.end_col).max().unwrap_or(0);
+
+        if let Some(last) = pending.last() {
+            if last.end_col == max_end {
+                row.message(&last.label.message, last.style(self));
+                pending.pop();
+            }
+        }
+        self.row(w, row)?;
+
+        while let Some(mark) = pending.pop() {
+            let mut connector = self.margin_row(multi, open);
+
+            for other in pending.iter().chain([&mark]) {
+                connector.put(self.margin + other.start_col, '|', other.style(self));
+            }
+            self.row(w, connector)?;
+
+            let mut row = self.margin_row(multi, open);
+
+            for other in &pending {
+                row.put(self.margin + other.start_col, '|', other.style(self));
+            }
+            row.pad(self.margin + mark.start_col);
+            row.cells.truncate(self.margin + mark.start_col);
+
+            for ch in mark.label.message.chars() {
+                row.cells.push((ch, mark.style(self)));
+            }
+            self.row(w, row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Label, Snippet};
+    use crate::style::{Style, Styles};
+
+    fn render(snippet: &Snippet) -> String {
+        let mut s = String::new();
+        snippet
+            .render(&mut s, "|", &Styles::PLAIN, Style::new())
+            .unwrap();
+        s
+    }
+
+    #[test]
+    fn stacked_labels() {
+        let snippet = Snippet::new("main.rs", "let x: i32 = \"a\";\n")
+            .and_label(Label::secondary(7..10, "expected due to this"))
+            .and_label(Label::primary(13..16, "expected `i32`, found `&str`"));
+
+        assert_eq!(
+            render(&snippet),
+            " --> main.rs:1:14
+  |
+1 | let x: i32 = \"a\";
+  |        ---   ^^^ expected `i32`, found `&str`
+  |        |
+  |        expected due to this
+"
+        );
+    }
+
+    #[test]
+    fn tabs_and_wide_characters() {
+        let snippet = Snippet::new("a.txt", "\t日本 = x").and_label(Label::primary(1..7, "wide"));
+
+        assert_eq!(
+            render(&snippet),
+            " --> a.txt:1:2
+  |
+1 |     日本 = x
+  |     ^^^^ wide
+"
+        );
+    }
+
+    #[test]
+    fn multi_line() {
+        let source = "fn main() {\n    foo();\n}\n";
+        let snippet = Snippet::new("main.rs", source)
+            .and_label(Label::primary(10..source.len(), "this block"));
+
+        assert_eq!(
+            render(&snippet),
+            " --> main.rs:1:11
+  |
+1 |   fn main() {
+  |  ___________^
+2 | |     foo();
+3 | | }
+  | |_^ this block
+"
+        );
+    }
+
+    #[test]
+    fn elided_multi_line() {
+        let source = "a {\n  b {\n    1\n    2\n    3\n    4\n  }\n}\n";
+        let snippet = Snippet::new("main.rs", source)
+            .and_label(Label::primary(2..source.len() - 1, "outer"))
+            .and_label(Label::secondary(6..source.len() - 3, "inner"));
+
+        assert_eq!(
+            render(&snippet),
+            " --> main.rs:1:3
+  |
+1 |     a {
+  |  _____^
+2 | | /   b {
+3 | | |     1
+... | |
+6 | | |     4
+7 | | |   }
+  | | |___- inner
+8 | |   }
+  | |___^ outer
+"
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! ANSI styling for rendered [UserError](crate::UserError)s.
+//!
+//! Styling is implemented with plain escape sequences, so no terminal library is required.
+use std::env;
+use std::fmt::{self, Display};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(ColorChoice::Auto as u8);
+
+/// One of the eight standard ANSI terminal colors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    const fn fg_code(self) -> u8 {
+        30 + self as u8
+    }
+}
+
+/// A foreground color paired with text attributes.
+/// # Examples
+/// ```
+/// use uerr::style::{Color, Style};
+///
+/// let style = Style::new().fg(Color::Red).bold();
+/// assert_eq!(style.paint("error").to_string(), "\x1b[1;31merror\x1b[0m");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Style {
+    fg: Option<Color>,
+    bold: bool,
+    dimmed: bool,
+}
+
+impl Style {
+    /// Create a new Style which does not alter the text.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            fg: None,
+            bold: false,
+            dimmed: false,
+        }
+    }
+
+    /// Set the foreground color.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Render the text in bold.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Render the text dimmed.
+    ///
+    /// Returns the current instance.
+    #[inline]
+    pub const fn dimmed(mut self) -> Self {
+        self.dimmed = true;
+        self
+    }
+
+    /// Returns true if this Style does not alter the text.
+    #[inline]
+    pub const fn is_plain(&self) -> bool {
+        self.fg.is_none() && !self.bold && !self.dimmed
+    }
+
+    /// Wrap the given value so that it is displayed with this Style.
+    #[inline]
+    pub const fn paint<D>(self, value: D) -> Painted<D> {
+        Painted { style: self, value }
+    }
+}
+
+/// A value displayed with a [Style]. See [Style::paint].
+pub struct Painted<D> {
+    style: Style,
+    value: D,
+}
+
+impl<D> Display for Painted<D>
+where
+    D: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.style.is_plain() {
+            return self.value.fmt(f);
+        }
+
+        f.write_str("\x1b[")?;
+
+        let mut codes = Vec::with_capacity(3);
+
+        if self.style.bold {
+            codes.push(1);
+        }
+
+        if self.style.dimmed {
+            codes.push(2);
+        }
+
+        if let Some(fg) = self.style.fg {
+            codes.push(fg.fg_code());
+        }
+
+        for (i, code) in codes.iter().enumerate() {
+            if i != 0 {
+                f.write_str(";")?;
+            }
+            write!(f, "{code}")?;
+        }
+
+        write!(f, "m{}\x1b[0m", self.value)
+    }
+}
+
+/// The styles used for each section of a rendered [UserError](crate::UserError).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Styles {
+    /// The style of the prefix and message of errors.
+    pub message: Style,
+    /// The style of the prefix and message of warnings.
+    pub warning: Style,
+    /// The style of the prefix and message of info diagnostics.
+    pub info: Style,
+    /// The style of the prefix and message of notes.
+    pub note: Style,
+    /// The style of the "caused by" label and gutter.
+    pub reason: Style,
+    /// The style of the "help" label and gutter.
+    pub help: Style,
+    /// The style of line numbers, gutters and secondary labels in source snippets.
+    pub gutter: Style,
+}
+
+impl Styles {
+    /// Styles which do not alter the text.
+    pub const PLAIN: Self = Self {
+        message: Style::new(),
+        warning: Style::new(),
+        info: Style::new(),
+        note: Style::new(),
+        reason: Style::new(),
+        help: Style::new(),
+        gutter: Style::new(),
+    };
+
+    /// The default colored styles: a red-bold message, yellow reasons and cyan help.
+    pub const COLORED: Self = Self {
+        message: Style::new().fg(Color::Red).bold(),
+        warning: Style::new().fg(Color::Yellow).bold(),
+        info: Style::new().fg(Color::Blue).bold(),
+        note: Style::new().fg(Color::Green).bold(),
+        reason: Style::new().fg(Color::Yellow),
+        help: Style::new().fg(Color::Cyan),
+        gutter: Style::new().fg(Color::Blue).bold(),
+    };
+}
+
+impl Default for Styles {
+    #[inline]
+    fn default() -> Self {
+        Self::COLORED
+    }
+}
+
+/// Whether [UserError::print_all](crate::UserError::print_all) should emit colors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ColorChoice {
+    /// Emit colors if stderr is a terminal and the environment does not disable them.
+    #[default]
+    Auto,
+    /// Always emit colors.
+    Always,
+    /// Never emit colors.
+    Never,
+}
+
+impl ColorChoice {
+    /// Resolve this choice against the environment and the given stream.
+    ///
+    /// For [ColorChoice::Auto], a non-empty `CLICOLOR_FORCE` other than `0` enables colors.
+    /// Otherwise, colors are disabled by a non-empty `NO_COLOR`, by `TERM=dumb`, or when the
+    /// stream is not a terminal.
+    pub fn enabled_for<S>(self, stream: &S) -> bool
+    where
+        S: IsTerminal,
+    {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => detect(
+                env::var_os("NO_COLOR").as_deref(),
+                env::var_os("CLICOLOR_FORCE").as_deref(),
+                env::var_os("TERM").as_deref(),
+                stream.is_terminal(),
+            ),
+        }
+    }
+
+    /// Resolve this choice against the environment and stderr. See [ColorChoice::enabled_for].
+    #[inline]
+    pub fn enabled_for_stderr(self) -> bool {
+        self.enabled_for(&std::io::stderr())
+    }
+}
+
+fn detect(
+    no_color: Option<&std::ffi::OsStr>,
+    clicolor_force: Option<&std::ffi::OsStr>,
+    term: Option<&std::ffi::OsStr>,
+    is_terminal: bool,
+) -> bool {
+    if clicolor_force.is_some_and(|v| !v.is_empty() && v != "0") {
+        return true;
+    }
+
+    if no_color.is_some_and(|v| !v.is_empty()) || term.is_some_and(|v| v == "dumb") {
+        return false;
+    }
+    is_terminal
+}
+
+/// Set the global [ColorChoice] used by [UserError::print_all](crate::UserError::print_all).
+#[inline]
+pub fn set_color_choice(choice: ColorChoice) {
+    COLOR_CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+/// Get the global [ColorChoice]. Defaults to [ColorChoice::Auto].
+#[inline]
+pub fn color_choice() -> ColorChoice {
+    match COLOR_CHOICE.load(Ordering::Relaxed) {
+        1 => ColorChoice::Always,
+        2 => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect, Color, Style};
+    use std::ffi::OsStr;
+
+    #[test]
+    fn paint() {
+        assert_eq!(Style::new().paint("x").to_string(), "x");
+        assert_eq!(
+            Style::new().fg(Color::Cyan).paint("x").to_string(),
+            "\x1b[36mx\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn detection() {
+        let s = |v| Some(OsStr::new(v));
+
+        assert!(detect(None, None, None, true));
+        assert!(!detect(None, None, None, false));
+        assert!(!detect(s("1"), None, None, true));
+        assert!(detect(s(""), None, None, true));
+        assert!(!detect(None, None, s("dumb"), true));
+        assert!(detect(s("1"), s("1"), s("dumb"), false));
+        assert!(!detect(None, s("0"), None, false));
+    }
+}
@@ -0,0 +1,154 @@
+//! Utilities for testing code paths which exit the process.
+//!
+//! Within [expect_exit], [exit](crate::exit::exit) and therefore
+//! [UserError::exit](crate::UserError::exit) and [unwrap_io](crate::unwrap_io) unwind with an
+//! [Exit] payload instead of terminating the process, and rendered errors are captured instead
+//! of being written to stderr. Capturing is local to the current thread.
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+
+thread_local! {
+    static CAPTURE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// The payload of a captured exit.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Exit {
+    code: i32,
+    output: String,
+}
+
+impl Exit {
+    /// The code the process would have exited with.
+    #[inline]
+    pub const fn code(&self) -> i32 {
+        self.code
+    }
+
+    /// Everything rendered to stderr before exiting, without colors.
+    #[inline]
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+}
+
+/// Run the given function, expecting it to exit, and return the captured [Exit].
+///
+/// Exit hooks are not run while capturing.
+/// # Panics
+/// Panics if the function returns without exiting. Other panics are propagated.
+/// # Examples
+/// ```
+/// use uerr::testing::expect_exit;
+/// use uerr::UserError;
+///
+/// let exit = expect_exit(|| {
+///     UserError::from("could not open file").print_all("error: ").exit(66);
+/// });
+///
+/// assert_eq!(exit.code(), 66);
+/// assert_eq!(exit.output(), "error: could not open file\n");
+/// ```
+pub fn expect_exit<F, R>(f: F) -> Exit
+where
+    F: FnOnce() -> R,
+{
+    let previous = CAPTURE.with(|capture| capture.replace(Some(String::new())));
+    let res = panic::catch_unwind(AssertUnwindSafe(f));
+    CAPTURE.with(|capture| *capture.borrow_mut() = previous);
+
+    match res {
+        Ok(_) => panic!("expected the function to exit"),
+        Err(payload) => match payload.downcast::<Exit>() {
+            Ok(exit) => *exit,
+            Err(payload) => panic::resume_unwind(payload),
+        },
+    }
+}
+
+/// Returns true if exits are being captured on the current thread.
+#[inline]
+pub fn is_capturing() -> bool {
+    CAPTURE.with(|capture| capture.borrow().is_some())
+}
+
+/// Append to the captured output with the given function.
+///
+/// Returns false, without calling the function, if exits are not being captured.
+pub(crate) fn capture<F>(f: F) -> bool
+where
+    F: FnOnce(&mut String),
+{
+    CAPTURE.with(|capture| match capture.borrow_mut().as_mut() {
+        Some(output) => {
+            f(output);
+            true
+        }
+        None => false,
+    })
+}
+
+/// Unwind with an [Exit] payload if exits are being captured on the current thread.
+pub(crate) fn exit(code: i32) {
+    let output = CAPTURE.with(|capture| capture.borrow_mut().take());
+
+    if let Some(output) = output {
+        panic::resume_unwind(Box::new(Exit { code, output }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expect_exit, is_capturing};
+    use std::io;
+
+    #[test]
+    fn unwrap_io() {
+        let exit = expect_exit(|| {
+            crate::unwrap_io::<()>("error: ", Err(io::Error::from(io::ErrorKind::NotFound)))
+        });
+
+        assert_eq!(exit.code(), 66);
+        assert!(exit.output().starts_with("error: entity not found\n"));
+        assert!(!is_capturing());
+    }
+
+    #[test]
+    fn unwrap_or_exit() {
+        use crate::exit::{ExitCode, FromError};
+        use crate::UserError;
+
+        let exit = expect_exit(|| {
+            let err = UserError::from("could not parse a.toml")
+                .and_reason("expected `=`")
+                .and_code("E01")
+                .and_exit_code(ExitCode::DataErr);
+
+            crate::unwrap_or_exit::<(), _, _>("error: ", Err(err), FromError)
+        });
+
+        assert_eq!(exit.code(), 65);
+        assert_eq!(
+            exit.output(),
+            "error[E01]: could not parse a.toml\n - caused by: expected `=`\n"
+        );
+    }
+
+    #[test]
+    fn expect_or_exit() {
+        use crate::exit::ExitCode;
+
+        let exit = expect_exit(|| {
+            crate::expect_or_exit::<()>("error: ", None, "no path given", ExitCode::Usage)
+        });
+
+        assert_eq!(exit.code(), 64);
+        assert_eq!(exit.output(), "error: no path given\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected the function to exit")]
+    fn no_exit() {
+        expect_exit(|| ());
+    }
+}
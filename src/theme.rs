@@ -0,0 +1,346 @@
+//! Themes controlling the layout of a rendered [UserError](crate::UserError).
+use std::borrow::Cow;
+use std::sync::{PoisonError, RwLock};
+
+use crate::style::Styles;
+use crate::width::{self, str_width};
+
+static THEME: RwLock<Theme> = RwLock::new(Theme::ASCII);
+
+/// The labels, glyphs, indentation and [Styles] used to render a
+/// [UserError](crate::UserError).
+///
+/// Each section line is laid out as `{indent}{bullet} {label}{separator}{text}`. Continuation
+/// lines, including those of text wrapped at [Theme::max_width], place the gutter glyph at
+/// [Theme::gutter_column] and align their text with the first.
+/// # Examples
+/// ```
+/// use uerr::theme::Theme;
+/// use uerr::UserError;
+///
+/// let mut s = String::new();
+///
+/// UserError::from("could not open file")
+///     .and_reason("The system cannot find the file specified.")
+///     .fmt_with(&mut s, "error: ", &Theme::UNICODE.without_styles())
+///     .unwrap();
+///
+/// assert_eq!(
+///     s,
+///     "error: could not open file\n ╰─▶ caused by: The system cannot find the file specified.\n"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Theme {
+    /// The label of the reasons section.
+    pub reason_label: Cow<'static, str>,
+    /// The label of the help section.
+    pub help_label: Cow<'static, str>,
+    /// The bullet preceding the reason label. May be empty.
+    pub reason_bullet: Cow<'static, str>,
+    /// The bullet preceding the help label. May be empty.
+    pub help_bullet: Cow<'static, str>,
+    /// The text between a label and its text.
+    pub separator: Cow<'static, str>,
+    /// The glyph drawn on continuation lines. May be empty.
+    pub gutter: Cow<'static, str>,
+    /// The connector preceding each cause in the tree of causes, but the last.
+    pub tree_branch: Cow<'static, str>,
+    /// The connector preceding the last cause in the tree of causes.
+    pub tree_last: Cow<'static, str>,
+    /// The connector preceding the continuation lines of each cause, but the last. The
+    /// continuation lines of the last are indented by the width of [Theme::tree_last].
+    pub tree_pipe: Cow<'static, str>,
+    /// The number of spaces preceding each bullet.
+    pub indent: usize,
+    /// The column at which the gutter is drawn.
+    pub gutter_column: usize,
+    /// Whether the location at which each UserError was created is rendered under its message,
+    /// as in `at src/config.rs:42:9`. See [UserError::location](crate::UserError::location).
+    pub show_location: bool,
+    /// The maximum width of rendered lines, beyond which the message, reasons and help are
+    /// wrapped at spaces. When printing to a terminal, its narrower width applies.
+    pub max_width: Option<usize>,
+    /// Whether trailing whitespace is removed from each line of the message, reasons and help.
+    pub trim_trailing_whitespace: bool,
+    /// How blank lines within the message, reasons and help are rendered.
+    pub blank_lines: BlankLines,
+    /// The depth of the tree of causes beyond which causes are summarized as `... and N more`.
+    pub max_cause_depth: Option<usize>,
+    /// The number of causes rendered at each level of the tree of causes, beyond which the
+    /// remainder is summarized as `... and N more`.
+    pub max_causes: Option<usize>,
+    /// The styles applied when colors are enabled.
+    pub styles: Styles,
+}
+
+impl Theme {
+    /// The classic ASCII look.
+    /// ```text
+    /// error: could not open file
+    ///  - caused by: The system cannot find the file specified.
+    ///      |        Filler reason.
+    ///  + help: Does this file exist?
+    /// ```
+    pub const ASCII: Self = Self {
+        reason_label: Cow::Borrowed("caused by"),
+        help_label: Cow::Borrowed("help"),
+        reason_bullet: Cow::Borrowed("-"),
+        help_bullet: Cow::Borrowed("+"),
+        separator: Cow::Borrowed(": "),
+        gutter: Cow::Borrowed("|"),
+        tree_branch: Cow::Borrowed("|- "),
+        tree_last: Cow::Borrowed("`- "),
+        tree_pipe: Cow::Borrowed("|  "),
+        indent: 1,
+        gutter_column: 5,
+        show_location: false,
+        max_width: None,
+        trim_trailing_whitespace: true,
+        blank_lines: BlankLines::Collapse,
+        max_cause_depth: None,
+        max_causes: None,
+        styles: Styles::COLORED,
+    };
+
+    /// A look using Unicode box-drawing characters.
+    /// ```text
+    /// error: could not open file
+    ///  ╰─▶ caused by: The system cannot find the file specified.
+    ///      │          Filler reason.
+    ///  ╰─▶ help: Does this file exist?
+    /// ```
+    pub const UNICODE: Self = Self {
+        reason_bullet: Cow::Borrowed("╰─▶"),
+        help_bullet: Cow::Borrowed("╰─▶"),
+        gutter: Cow::Borrowed("│"),
+        tree_branch: Cow::Borrowed("├─ "),
+        tree_last: Cow::Borrowed("└─ "),
+        tree_pipe: Cow::Borrowed("│  "),
+        ..Self::ASCII
+    };
+
+    /// A minimal look without bullets or gutters.
+    /// ```text
+    /// error: could not open file
+    ///   caused by: The system cannot find the file specified.
+    ///              Filler reason.
+    ///   help: Does this file exist?
+    /// ```
+    pub const MINIMAL: Self = Self {
+        reason_bullet: Cow::Borrowed(""),
+        help_bullet: Cow::Borrowed(""),
+        gutter: Cow::Borrowed(""),
+        tree_branch: Cow::Borrowed("  "),
+        tree_last: Cow::Borrowed("  "),
+        tree_pipe: Cow::Borrowed("  "),
+        indent: 2,
+        ..Self::ASCII
+    };
+
+    /// Returns this Theme with [Styles::PLAIN].
+    #[inline]
+    pub fn without_styles(mut self) -> Self {
+        self.styles = Styles::PLAIN;
+        self
+    }
+
+    /// Returns this Theme with the given [Styles].
+    #[inline]
+    pub fn with_styles(mut self, styles: Styles) -> Self {
+        self.styles = styles;
+        self
+    }
+
+    /// Returns this Theme with [Theme::show_location] set to the given value.
+    #[inline]
+    pub fn with_location(mut self, show: bool) -> Self {
+        self.show_location = show;
+        self
+    }
+
+    /// Returns this Theme with [Theme::max_width] set to the given width.
+    #[inline]
+    pub fn with_max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Split the given text into its rendered lines, following a leader of the given width.
+    ///
+    /// Embedded newlines are honored, blank lines are handled according to
+    /// [Theme::blank_lines], and each line is wrapped at the given width. At least one line is
+    /// returned.
+    pub(crate) fn lines<'a>(
+        &self,
+        text: &'a str,
+        leader: usize,
+        width: Option<usize>,
+    ) -> Vec<&'a str> {
+        let mut lines = Vec::new();
+        let mut blank = false;
+
+        for line in text.lines() {
+            let line = if self.trim_trailing_whitespace {
+                line.trim_end()
+            } else {
+                line
+            };
+
+            if line.trim_start().is_empty() {
+                match self.blank_lines {
+                    BlankLines::Remove => continue,
+                    BlankLines::Collapse if blank || lines.is_empty() => continue,
+                    _ => {}
+                }
+
+                blank = true;
+                lines.push(line);
+                continue;
+            }
+            blank = false;
+
+            match width {
+                Some(width) => lines.extend(width::wrap(line, width.saturating_sub(leader).max(1))),
+                None => lines.push(line),
+            }
+        }
+
+        if blank && self.blank_lines == BlankLines::Collapse {
+            lines.pop();
+        }
+
+        if lines.is_empty() {
+            lines.push("");
+        }
+        lines
+    }
+
+    /// The leader to render before the given line, without trailing whitespace if the line is
+    /// empty and [Theme::trim_trailing_whitespace] is set.
+    pub(crate) fn blank_leader<'a>(&self, leader: &'a str, line: &str) -> &'a str {
+        if line.is_empty() && self.trim_trailing_whitespace {
+            leader.trim_end()
+        } else {
+            leader
+        }
+    }
+
+    /// Build the first-line and continuation-line leaders of a section.
+    pub(crate) fn leaders(&self, bullet: &str, label: &str) -> (String, String) {
+        let mut first = " ".repeat(self.indent);
+
+        if !bullet.is_empty() {
+            first.push_str(bullet);
+            first.push(' ');
+        }
+        first.push_str(label);
+        first.push_str(&self.separator);
+
+        let width = str_width(&first);
+        let mut rest = String::with_capacity(width);
+
+        if self.gutter.is_empty() {
+            rest.extend(std::iter::repeat_n(' ', width));
+        } else {
+            rest.extend(std::iter::repeat_n(' ', self.gutter_column));
+            rest.push_str(&self.gutter);
+
+            let used = self.gutter_column + str_width(&self.gutter);
+            rest.extend(std::iter::repeat_n(' ', width.saturating_sub(used)));
+        }
+        (first, rest)
+    }
+}
+
+/// How blank lines within the message, reasons and help of a [UserError](crate::UserError) are
+/// rendered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BlankLines {
+    /// Keep every blank line.
+    Keep,
+    /// Collapse runs of blank lines into one, and drop those at the start and end of the text.
+    #[default]
+    Collapse,
+    /// Drop every blank line.
+    Remove,
+}
+
+impl Default for Theme {
+    #[inline]
+    fn default() -> Self {
+        Self::ASCII
+    }
+}
+
+/// Set the global [Theme] used by [UserError::print_all](crate::UserError::print_all) and
+/// the other renderers which do not take a Theme.
+pub fn set_theme(theme: Theme) {
+    *THEME.write().unwrap_or_else(PoisonError::into_inner) = theme;
+}
+
+/// Get a copy of the global [Theme]. Defaults to [Theme::ASCII].
+pub fn theme() -> Theme {
+    with_theme(Theme::clone)
+}
+
+/// Run the given function with a reference to the global [Theme].
+///
+/// The lock is held while the function runs, so it must not render or run any caller code;
+/// clone the Theme with [theme] for that instead.
+pub(crate) fn with_theme<F, R>(f: F) -> R
+where
+    F: FnOnce(&Theme) -> R,
+{
+    f(&THEME.read().unwrap_or_else(PoisonError::into_inner))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{self, Display};
+
+    use super::{set_theme, theme, Theme};
+    use crate::UserError;
+
+    #[test]
+    fn ascii_leaders() {
+        let theme = Theme::ASCII;
+
+        assert_eq!(
+            theme.leaders(&theme.reason_bullet, &theme.reason_label),
+            (" - caused by: ".into(), "     |        ".into())
+        );
+        assert_eq!(
+            theme.leaders(&theme.help_bullet, &theme.help_label),
+            (" + help: ".into(), "     |   ".into())
+        );
+    }
+
+    #[test]
+    fn minimal_leaders() {
+        let theme = Theme::MINIMAL;
+
+        assert_eq!(
+            theme.leaders(&theme.help_bullet, &theme.help_label),
+            ("  help: ".into(), "        ".into())
+        );
+    }
+
+    #[test]
+    fn lock_released_while_rendering() {
+        struct Prefix;
+
+        impl Display for Prefix {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                // Writing the theme deadlocks if the renderer still holds the lock.
+                set_theme(theme());
+                f.write_str("error: ")
+            }
+        }
+
+        let mut s = String::new();
+        UserError::from("x").fmt_to(&mut s, Prefix).unwrap();
+
+        assert_eq!(s, "error: x\n");
+    }
+}
@@ -0,0 +1,289 @@
+//! Display width calculations for terminal output.
+
+/// The number of columns a tab is expanded to.
+pub(crate) const TAB_WIDTH: usize = 4;
+
+/// The number of terminal columns occupied by the given character.
+///
+/// Control characters and combining marks occupy no columns, while East Asian wide characters
+/// and most emoji occupy two.
+pub(crate) fn char_width(c: char) -> usize {
+    let c = c as u32;
+
+    if c < 0x20 || (0x7f..0xa0).contains(&c) {
+        return 0;
+    }
+
+    if c < 0x300 {
+        return 1;
+    }
+
+    const ZERO: &[(u32, u32)] = &[
+        (0x0300, 0x036f),
+        (0x0483, 0x0489),
+        (0x0591, 0x05bd),
+        (0x0610, 0x061a),
+        (0x064b, 0x065f),
+        (0x0e31, 0x0e31),
+        (0x0e34, 0x0e3a),
+        (0x1ab0, 0x1aff),
+        (0x1dc0, 0x1dff),
+        (0x200b, 0x200f),
+        (0x2028, 0x202e),
+        (0x2060, 0x2064),
+        (0x20d0, 0x20ff),
+        (0xfe00, 0xfe0f),
+        (0xfe20, 0xfe2f),
+        (0xfeff, 0xfeff),
+        (0xe0100, 0xe01ef),
+    ];
+
+    const WIDE: &[(u32, u32)] = &[
+        (0x1100, 0x115f),
+        (0x231a, 0x231b),
+        (0x2329, 0x232a),
+        (0x23e9, 0x23ec),
+        (0x25fd, 0x25fe),
+        (0x2614, 0x2615),
+        (0x2648, 0x2653),
+        (0x26a1, 0x26a1),
+        (0x26aa, 0x26ab),
+        (0x26bd, 0x26be),
+        (0x26c4, 0x26c5),
+        (0x26d4, 0x26d4),
+        (0x26ea, 0x26ea),
+        (0x26f2, 0x26f5),
+        (0x26fa, 0x26fd),
+        (0x2705, 0x2705),
+        (0x270a, 0x270b),
+        (0x2728, 0x2728),
+        (0x274c, 0x274c),
+        (0x2753, 0x2755),
+        (0x2757, 0x2757),
+        (0x2795, 0x2797),
+        (0x27b0, 0x27b0),
+        (0x27bf, 0x27bf),
+        (0x2b1b, 0x2b1c),
+        (0x2e80, 0x303e),
+        (0x3041, 0x33ff),
+        (0x3400, 0x4dbf),
+        (0x4e00, 0x9fff),
+        (0xa000, 0xa4cf),
+        (0xa960, 0xa97f),
+        (0xac00, 0xd7a3),
+        (0xf900, 0xfaff),
+        (0xfe10, 0xfe19),
+        (0xfe30, 0xfe6f),
+        (0xff00, 0xff60),
+        (0xffe0, 0xffe6),
+        (0x1f004, 0x1f004),
+        (0x1f0cf, 0x1f0cf),
+        (0x1f18e, 0x1f18e),
+        (0x1f191, 0x1f19a),
+        (0x1f200, 0x1f251),
+        (0x1f300, 0x1f64f),
+        (0x1f680, 0x1f6ff),
+        (0x1f900, 0x1f9ff),
+        (0x1fa70, 0x1faff),
+        (0x20000, 0x3fffd),
+    ];
+
+    let within = |table: &[(u32, u32)]| {
+        table
+            .binary_search_by(|&(lo, hi)| {
+                if hi < c {
+                    std::cmp::Ordering::Less
+                } else if lo > c {
+                    std::cmp::Ordering::Greater
+                } else {
+                    std::cmp::Ordering::Equal
+                }
+            })
+            .is_ok()
+    };
+
+    if within(ZERO) {
+        0
+    } else if within(WIDE) {
+        2
+    } else {
+        1
+    }
+}
+
+/// The number of terminal columns occupied by the given text, with tabs expanded to
+/// [TAB_WIDTH] columns.
+pub(crate) fn str_width(s: &str) -> usize {
+    s.chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { char_width(c) })
+        .sum()
+}
+
+/// Split the given text into lines no wider than the given width, breaking at spaces.
+///
+/// Words wider than the width are kept whole on a line of their own. The spaces at which a line
+/// is broken are dropped, while those at the end of the text are kept.
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut end = 0;
+    let mut used = 0;
+
+    for (i, word) in text.split(' ').scan(0, |pos, word| {
+        let i = *pos;
+        *pos += word.len() + 1;
+        Some((i, word))
+    }) {
+        if word.is_empty() {
+            continue;
+        }
+
+        let word_width = str_width(word);
+        let gap = str_width(&text[end..i]);
+
+        if end > start && used + gap + word_width > width {
+            lines.push(&text[start..end]);
+            start = i;
+            used = word_width;
+        } else {
+            used += gap + word_width;
+        }
+        end = i + word.len();
+    }
+
+    lines.push(&text[start..]);
+    lines
+}
+
+/// The width of the terminal attached to stderr, overridden by a positive `COLUMNS`.
+pub(crate) fn terminal_width() -> Option<usize> {
+    let columns = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok());
+
+    match columns {
+        Some(columns) if columns > 0 => Some(columns),
+        _ => sys::stderr_width(),
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+))]
+mod sys {
+    use std::ffi::{c_int, c_ulong};
+    use std::io::IsTerminal;
+
+    #[repr(C)]
+    struct Winsize {
+        ws_row: u16,
+        ws_col: u16,
+        ws_xpixel: u16,
+        ws_ypixel: u16,
+    }
+
+    // Linux uses the BSD encoding of the request on powerpc, mips and sparc.
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        not(any(
+            target_arch = "powerpc",
+            target_arch = "powerpc64",
+            target_arch = "mips",
+            target_arch = "mips32r6",
+            target_arch = "mips64",
+            target_arch = "mips64r6",
+            target_arch = "sparc",
+            target_arch = "sparc64"
+        ))
+    ))]
+    const TIOCGWINSZ: c_ulong = 0x5413;
+    #[cfg(not(all(
+        any(target_os = "linux", target_os = "android"),
+        not(any(
+            target_arch = "powerpc",
+            target_arch = "powerpc64",
+            target_arch = "mips",
+            target_arch = "mips32r6",
+            target_arch = "mips64",
+            target_arch = "mips64r6",
+            target_arch = "sparc",
+            target_arch = "sparc64"
+        ))
+    )))]
+    const TIOCGWINSZ: c_ulong = 0x4008_7468;
+
+    extern "C" {
+        fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    }
+
+    pub(super) fn stderr_width() -> Option<usize> {
+        if !std::io::stderr().is_terminal() {
+            return None;
+        }
+
+        let mut size = Winsize {
+            ws_row: 0,
+            ws_col: 0,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+
+        // SAFETY: TIOCGWINSZ writes a `winsize` through the pointer, which outlives the call.
+        let res = unsafe { ioctl(2, TIOCGWINSZ, &mut size as *mut Winsize) };
+
+        (res == 0 && size.ws_col > 0).then_some(size.ws_col as usize)
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+)))]
+mod sys {
+    pub(super) fn stderr_width() -> Option<usize> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{str_width, wrap};
+
+    #[test]
+    fn widths() {
+        assert_eq!(str_width("abc"), 3);
+        assert_eq!(str_width("\tx"), 5);
+        assert_eq!(str_width("日本"), 4);
+        assert_eq!(str_width("e\u{301}"), 1);
+        assert_eq!(str_width("🦀"), 2);
+    }
+
+    #[test]
+    fn wrapping() {
+        assert_eq!(
+            wrap("the quick brown fox jumps", 10),
+            ["the quick", "brown fox", "jumps"]
+        );
+        assert_eq!(wrap("a  b", 4), ["a  b"]);
+        assert_eq!(
+            wrap("tiny averyverylongword x", 6),
+            ["tiny", "averyverylongword", "x"]
+        );
+        assert_eq!(wrap("日本語 テキスト", 8), ["日本語", "テキスト"]);
+        assert_eq!(wrap("", 8), [""]);
+        assert_eq!(wrap("ab cd  ", 3), ["ab", "cd  "]);
+    }
+}
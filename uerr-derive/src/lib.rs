@@ -0,0 +1,239 @@
+//! Derive macro for converting error types into `uerr::UserError`.
+//!
+//! See the `derive` feature of the `uerr` crate.
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Error, Expr, Fields, Ident, LitStr};
+
+/// Implement `From<T> for uerr::UserError`.
+///
+/// Attributes may be placed on the type, applying to every variant, and on each variant:
+/// - `message = "..."`: the message; defaults to the `Display` text of the value.
+/// - `reason = "..."` and `help = "..."`: added in order; may be repeated.
+/// - `code = "..."`: the error code, overriding that of the type.
+/// - `exit = ...`: the exit code, overriding that of the type.
+///
+/// Strings may interpolate the fields of the variant, as in `"{path}"` or `"{0}"`.
+///
+/// A field marked `#[uerr(source)]` must implement `std::error::Error` or be a boxed
+/// `dyn Error`; its message and source chain are added as the first reasons.
+#[proc_macro_derive(UserError, attributes(uerr))]
+pub fn derive_user_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct Attrs {
+    message: Option<LitStr>,
+    reasons: Vec<LitStr>,
+    help: Vec<LitStr>,
+    code: Option<LitStr>,
+    exit: Option<Expr>,
+}
+
+impl Attrs {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("uerr")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("message") {
+                    parsed.message = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("reason") {
+                    parsed.reasons.push(meta.value()?.parse()?);
+                } else if meta.path.is_ident("help") {
+                    parsed.help.push(meta.value()?.parse()?);
+                } else if meta.path.is_ident("code") {
+                    parsed.code = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("exit") {
+                    parsed.exit = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(
+                        meta.error("expected `message`, `reason`, `help`, `code` or `exit`")
+                    );
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+fn is_source(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut source = false;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("uerr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("source") {
+                source = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `source`"))
+            }
+        })?;
+    }
+    Ok(source)
+}
+
+/// Rewrite positional interpolations such as `{0}` into the bindings `{_0}`.
+fn interpolate(lit: &LitStr) -> LitStr {
+    let value = lit.value();
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        out.push(c);
+
+        if c == '{' {
+            if chars.peek() == Some(&'{') {
+                out.push(chars.next().unwrap());
+            } else if chars.peek().is_some_and(char::is_ascii_digit) {
+                out.push('_');
+            }
+        }
+    }
+    LitStr::new(&out, lit.span())
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let container = Attrs::parse(&input.attrs)?;
+
+    let variants: Vec<(TokenStream2, &Fields, Attrs)> = match &input.data {
+        Data::Struct(data) => vec![(quote!(#name), &data.fields, Attrs::default())],
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let ident = &variant.ident;
+                Ok((
+                    quote!(#name::#ident),
+                    &variant.fields,
+                    Attrs::parse(&variant.attrs)?,
+                ))
+            })
+            .collect::<syn::Result<_>>()?,
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                input,
+                "UserError cannot be derived for unions",
+            ))
+        }
+    };
+
+    let mut needs_display = false;
+    let mut arms = Vec::with_capacity(variants.len());
+
+    for (path, fields, attrs) in variants {
+        let bindings: Vec<Ident> = fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| match &field.ident {
+                Some(ident) => ident.clone(),
+                None => format_ident!("_{}", i),
+            })
+            .collect();
+
+        let pattern = match fields {
+            Fields::Named(_) => quote!(#path { #(#bindings),* }),
+            Fields::Unnamed(_) => quote!(#path ( #(#bindings),* )),
+            Fields::Unit => quote!(#path),
+        };
+
+        let message = match attrs.message.as_ref().or(container.message.as_ref()) {
+            Some(message) => {
+                let message = interpolate(message);
+                quote!(::std::format!(#message))
+            }
+            None => {
+                needs_display = true;
+                quote!(__uerr_display)
+            }
+        };
+
+        let mut source = None;
+
+        for (field, binding) in fields.iter().zip(&bindings) {
+            if is_source(&field.attrs)? {
+                if source.is_some() {
+                    return Err(Error::new_spanned(field, "only one field may be a source"));
+                }
+                source = Some(binding);
+            }
+        }
+
+        let source = source.map(|binding| {
+            quote! {
+                let __uerr_cause = {
+                    use ::uerr::__private::AsDynError as _;
+                    ::uerr::UserError::from_error(#binding.as_dyn_error())
+                };
+                __uerr_reasons.push(__uerr_cause.message().clone());
+                __uerr_reasons.extend(__uerr_cause.reasons().iter().cloned());
+            }
+        });
+
+        let reasons = container
+            .reasons
+            .iter()
+            .chain(&attrs.reasons)
+            .map(interpolate);
+        let help = container.help.iter().chain(&attrs.help).map(interpolate);
+        let code = attrs
+            .code
+            .as_ref()
+            .or(container.code.as_ref())
+            .map(|code| quote!(__uerr_err.set_code(#code);));
+        let exit = attrs
+            .exit
+            .as_ref()
+            .or(container.exit.as_ref())
+            .map(|exit| quote!(__uerr_err.set_exit_code(#exit);));
+
+        // Everything which may interpolate the fields is computed before the error is bound, and
+        // the locals are mangled, so that no field is shadowed.
+        arms.push(quote! {
+            #pattern => {
+                let __uerr_message = #message;
+                let mut __uerr_reasons: ::std::vec::Vec<::std::string::String> =
+                    ::std::vec::Vec::new();
+                #source
+                #(__uerr_reasons.push(::std::format!(#reasons));)*
+                let __uerr_help: ::std::vec::Vec<::std::string::String> =
+                    ::std::vec![#(::std::format!(#help)),*];
+
+                let mut __uerr_err = ::uerr::UserError::new(__uerr_message);
+                __uerr_err.reasons_mut().extend(__uerr_reasons);
+                __uerr_err.help_mut().extend(__uerr_help);
+                #code
+                #exit
+                __uerr_err
+            }
+        });
+    }
+
+    let display = needs_display
+        .then(|| quote!(let __uerr_display = ::std::string::ToString::to_string(&value);));
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for ::uerr::UserError
+        #where_clause
+        {
+            #[allow(unused_mut, unused_variables)]
+            #[track_caller]
+            fn from(value: #name #ty_generics) -> Self {
+                #display
+                match value {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
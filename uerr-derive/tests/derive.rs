@@ -0,0 +1,143 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use uerr::exit::ExitCode;
+use uerr::UserError;
+
+#[derive(Debug, UserError)]
+enum ReadError {
+    #[uerr(message = "read failed", reason = "inner: {err}", help = "{err}")]
+    Read {
+        #[uerr(source)]
+        err: io::Error,
+    },
+    #[uerr(message = "bad {value}", reason = "{value} is not a number")]
+    Value { value: String },
+}
+
+#[test]
+fn fields_are_not_shadowed() {
+    let err: UserError = ReadError::Read {
+        err: io::Error::new(io::ErrorKind::NotFound, "no such file"),
+    }
+    .into();
+
+    assert_eq!(err.message(), "read failed");
+    assert_eq!(err.reasons(), &["no such file", "inner: no such file"]);
+    assert_eq!(err.help(), &["no such file"]);
+
+    let err: UserError = ReadError::Value { value: "x".into() }.into();
+
+    assert_eq!(err.message(), "bad x");
+    assert_eq!(err.reasons(), &["x is not a number"]);
+}
+
+#[derive(Debug, UserError)]
+#[uerr(message = "invalid port {port}", help = "Ports range from 1 to 65535.")]
+struct PortError {
+    port: u32,
+}
+
+#[derive(Debug, UserError)]
+#[uerr(message = "unknown key `{0}`", reason = "in {1}")]
+struct KeyError(String, &'static str);
+
+#[derive(Debug, UserError)]
+#[uerr(message = "interrupted", exit = 130)]
+struct Interrupted;
+
+#[derive(Debug, UserError)]
+struct Displayed;
+
+impl fmt::Display for Displayed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("displayed")
+    }
+}
+
+#[test]
+fn struct_forms() {
+    let err: UserError = PortError { port: 70000 }.into();
+
+    assert_eq!(err.message(), "invalid port 70000");
+    assert_eq!(err.help(), &["Ports range from 1 to 65535."]);
+
+    let err: UserError = KeyError("colour".into(), "a.toml").into();
+
+    assert_eq!(err.message(), "unknown key `colour`");
+    assert_eq!(err.reasons(), &["in a.toml"]);
+
+    let err: UserError = Interrupted.into();
+
+    assert_eq!(err.message(), "interrupted");
+    assert_eq!(err.exit_code(), Some(ExitCode::Other(130)));
+
+    let err: UserError = Displayed.into();
+    assert_eq!(err.message(), "displayed");
+}
+
+#[derive(Debug, UserError)]
+#[uerr(code = "E00", exit = ExitCode::Config, reason = "while loading the config")]
+#[uerr(help = "See `mytool --help`.")]
+enum ConfigError {
+    #[uerr(message = "missing key `{0}`", help = "Add `{0} = ...`.")]
+    Missing(&'static str),
+    #[uerr(message = "no config", code = "E01", exit = ExitCode::NoInput)]
+    NoConfig,
+}
+
+#[test]
+fn container_attributes() {
+    let err: UserError = ConfigError::Missing("name").into();
+
+    assert_eq!(err.message(), "missing key `name`");
+    assert_eq!(err.reasons(), &["while loading the config"]);
+    assert_eq!(err.help(), &["See `mytool --help`.", "Add `name = ...`."]);
+    assert_eq!(err.code(), Some("E00"));
+    assert_eq!(err.exit_code(), Some(ExitCode::Config));
+
+    let err: UserError = ConfigError::NoConfig.into();
+
+    assert_eq!(err.code(), Some("E01"));
+    assert_eq!(err.exit_code(), Some(ExitCode::NoInput));
+}
+
+#[derive(Debug)]
+struct Outer(io::Error);
+
+impl fmt::Display for Outer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("outer")
+    }
+}
+
+impl Error for Outer {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[derive(Debug, UserError)]
+enum BoxedError {
+    #[uerr(message = "send + sync")]
+    SendSync(#[uerr(source)] Box<dyn Error + Send + Sync>),
+    #[uerr(message = "plain")]
+    Plain(#[uerr(source)] Box<dyn Error>),
+    #[uerr(message = "borrowed")]
+    Borrowed(#[uerr(source)] &'static io::Error),
+}
+
+#[test]
+fn boxed_sources() {
+    let inner = || io::Error::other("inner");
+
+    let err: UserError = BoxedError::SendSync(Box::new(Outer(inner()))).into();
+    assert_eq!(err.reasons(), &["outer", "inner"]);
+
+    let err: UserError = BoxedError::Plain(Box::new(Outer(inner()))).into();
+    assert_eq!(err.reasons(), &["outer", "inner"]);
+
+    let err: UserError = BoxedError::Borrowed(Box::leak(Box::new(inner()))).into();
+    assert_eq!(err.reasons(), &["inner"]);
+}